@@ -14,8 +14,7 @@ interface SingleRicochetRobotConstructor {
 	slackClients: SlackInterface,
 	channel: string,
 	depth: number,
-	size: {h: number, w: number},
-	numOfWalls: number,
+	preset: string,
 	threadTs: string,
 	originalUser: string,
 }
@@ -43,8 +42,8 @@ export default class SinglePlayRicochetRobot extends AteQuiz {
 		this.originalUser = originalUser;
 	}
 
-	static async init({slackClients, channel, depth, size, numOfWalls, threadTs, originalUser}: SingleRicochetRobotConstructor) {
-		const [boardData, answer] = await board.getBoard({depth, size, numOfWalls});
+	static async init({slackClients, channel, depth, preset, threadTs, originalUser}: SingleRicochetRobotConstructor) {
+		const [boardData, answer] = await board.getBoard({depth, preset});
 		const imageData = await image.upload(boardData);
 		const quizText = `${answer.length}手詰めです`;
 
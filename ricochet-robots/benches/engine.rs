@@ -0,0 +1,145 @@
+//! Benchmarks of the ricochet robots engine.
+//!
+//! Save a baseline before trying an optimisation, and compare against it afterwards:
+//!
+//! ```text
+//! cargo bench --bench engine -- --save-baseline before
+//! cargo bench --bench engine -- --baseline before
+//! ```
+//!
+//! `ricochet_robot_benchmarker` still checks that the answers don't change.
+
+use std::collections::hash_map::DefaultHasher;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+
+extern crate xorshift;
+use xorshift::{Rng, SeedableRng, Xorshift128};
+
+extern crate ricochet_robots;
+use ricochet_robots::bench_hooks;
+use ricochet_robots::{bfs, Board, BoardRng, Move, Pos};
+
+/// A board size with the number of walls and the depth given to `bfs`.
+struct Scenario {
+	name: &'static str,
+	h: usize,
+	w: usize,
+	wall_num: usize,
+	depth: u8,
+}
+
+/// The sizes used by the bot, plus larger ones. 5x7 with depth 100 explores every state.
+const SCENARIOS: [Scenario; 5] = [
+	Scenario {
+		name: "5x7-exhaustive",
+		h: 5,
+		w: 7,
+		wall_num: 10,
+		depth: 100,
+	},
+	Scenario {
+		name: "7x9-depth6",
+		h: 7,
+		w: 9,
+		wall_num: 15,
+		depth: 6,
+	},
+	Scenario {
+		name: "7x9-depth8",
+		h: 7,
+		w: 9,
+		wall_num: 15,
+		depth: 8,
+	},
+	Scenario {
+		name: "10x10-depth8",
+		h: 10,
+		w: 10,
+		wall_num: 20,
+		depth: 8,
+	},
+	Scenario {
+		name: "16x16-depth6",
+		h: 16,
+		w: 16,
+		wall_num: 40,
+		depth: 6,
+	},
+];
+
+/// The same boards on every run, seeded like `ricochet_robot_benchmarker`.
+fn board_rng(t: u64) -> BoardRng {
+	let mut rng = Xorshift128::from_seed(&[t + 1, t + 1][..]);
+	for _ in 1..200 {
+		let _: u64 = rng.gen();
+	}
+	rng
+}
+
+fn board(scenario: &Scenario) -> Board {
+	Board::new(scenario.h, scenario.w, board_rng(0), scenario.wall_num)
+}
+
+fn generation(c: &mut Criterion) {
+	let mut group = c.benchmark_group("generation");
+	for scenario in &SCENARIOS {
+		group.bench_function(scenario.name, |b| {
+			b.iter(|| {
+				Board::new(
+					scenario.h,
+					scenario.w,
+					black_box(board_rng(0)),
+					scenario.wall_num,
+				)
+			})
+		});
+	}
+	group.finish();
+}
+
+fn search(c: &mut Criterion) {
+	let mut group = c.benchmark_group("bfs");
+	group.sample_size(20);
+	for scenario in &SCENARIOS {
+		let bo = board(scenario);
+		group.bench_with_input(BenchmarkId::from_parameter(scenario.name), &bo, |b, bo| {
+			b.iter(|| bfs(scenario.depth, black_box(bo)))
+		});
+	}
+	group.finish();
+}
+
+fn hashing(c: &mut Criterion) {
+	let bo = board(&SCENARIOS[2]);
+	c.bench_function("hash_state", |b| {
+		b.iter(|| bench_hooks::hash_start::<DefaultHasher>(black_box(&bo)))
+	});
+	c.bench_function("prev_round_trip", |b| {
+		let m = Move { c: 2, d: 3 };
+		let p = Pos { y: 5, x: 7 };
+		b.iter(|| bench_hooks::prev_round_trip(black_box(&m), black_box(&p)))
+	});
+}
+
+fn moves(c: &mut Criterion) {
+	let bo = board(&SCENARIOS[2]);
+	c.bench_function("move_to", |b| {
+		b.iter(|| {
+			let mut moved = 0;
+			for i in 0..4 {
+				for d in 0..4 {
+					moved += bench_hooks::move_start(black_box(&bo), i, d) as usize;
+				}
+			}
+			moved
+		})
+	});
+	c.bench_function("enumerate_states", |b| {
+		b.iter(|| bench_hooks::enumerate_start(black_box(&bo)))
+	});
+}
+
+criterion_group!(benches, generation, search, hashing, moves);
+criterion_main!(benches);
@@ -20,8 +20,8 @@ export interface Command {
 
 export interface BoardSpec {
 	depth: number,
-	size: {h: number, w: number},
-	numOfWalls: number,
+	// A preset name in ricochet-robots/presets.json, such as ハイパー.
+	preset: string,
 }
 
 interface GoalPosition extends Position {
@@ -205,16 +205,9 @@ export const logstringfy = (log: Move[]) => {
 };
 
 export const getBoard = async (boardspec: BoardSpec): Promise<[Board, Move[]]> => {
-	const data = await rust_proxy.get_data(boardspec);
-	let lines = data.split('\n').filter((line) => line);
-	lines = lines.slice(lines.length-4,lines.length);
-
-	const board_data = JSON.parse(lines[0].replace(/Pos|WallPos|Board/g,'').replace(/([a-z]+)/g,'"$1"'));
-	const goalcolour = parseInt(lines[1]);
-	const goalpos = JSON.parse(lines[2].replace(/Pos/g,'').replace(/([a-z]+)/g,'"$1"'));
-	const answer = JSON.parse(lines[3].replace(/Move/g,'').replace(/([a-z]+)/g,'"$1"'));
+	const {board: board_data, goal, answer} = await rust_proxy.get_data(boardspec);
 
 	const bo =  new Board();
-	bo.load_board(board_data,goalcolour,goalpos);
+	bo.load_board(board_data,goal.colour,{y: goal.y, x: goal.x, colour: goal.colour});
 	return [bo,answer];
 };
@@ -7,14 +7,15 @@ import path from 'path';
 import type { SectionBlock } from '@slack/web-api';
 
 vi.mock('cloudinary');
-vi.mock('./rust-proxy');
+// The addon itself is tested in rust-proxy.test.ts.
+vi.mock('./rust-proxy', () => ({get_data: vi.fn()}));
 vi.mock('../achievements');
 vi.mock('../lib/slackUtils');
 
 const get_data = vi.mocked(rust_proxy.get_data);
 get_data.mockImplementation((x) => {
 	return new Promise((resolve) => {
-		resolve(fs.readFileSync(path.join(__dirname,'rust_test_output.txt')).toString());
+		resolve(JSON.parse(fs.readFileSync(path.join(__dirname,'rust_test_output.json')).toString()));
 	});
 });
 
@@ -44,7 +45,7 @@ describe('hyperrobot', () => {
 			const blocks = 'blocks' in response ? response.blocks : [];
 
 			expect(get_data).toBeCalledTimes(1);
-			expect(get_data).toBeCalledWith({depth: 1000, size: {h: 7, w: 9}, numOfWalls: 15});
+			expect(get_data).toBeCalledWith({depth: 1000, preset: 'ハイパー'});
 
 			expect('username' in response && response.username).toBe('hyperrobot');
 			expect(response.text).toContain('10手詰めです');
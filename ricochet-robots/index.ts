@@ -199,18 +199,11 @@ class RicochetRobotsBot extends ChannelLimitedBot {
 						depth = 1;
 					}
 
-					const difficulty = {
-						"ベイビー": {size: {h: 3, w: 5}, numOfWalls: 3},
-						"スーパー": {size: {h: 5, w: 7}, numOfWalls: 10},
-						"ハイパー": {size: {h: 7, w: 9}, numOfWalls: 15},
-					}[text.match(/^(ベイビー|スーパー|ハイパー)/)[0]];
-
 					const singlePlayRicochetRobot = await SinglePlayRicochetRobot.init({
 						slackClients: this.slackClients,
 						channel,
 						depth,
-						size: difficulty.size,
-						numOfWalls: difficulty.numOfWalls,
+						preset: matches[1],
 						threadTs: message.ts,
 						originalUser: message.user,
 					});
@@ -238,14 +231,8 @@ class RicochetRobotsBot extends ChannelLimitedBot {
 					// TODO: バトルでない場合の処理を削除
 					const isbattle = text.match(/^(ベイビー|スーパー|ハイパー)ロボットバトル$/);
 
-					const difficulty = {
-						"ベイビー": {size: {h: 3, w: 5}, numOfWalls: 3},
-						"スーパー": {size: {h: 5, w: 7}, numOfWalls: 10},
-						"ハイパー": {size: {h: 7, w: 9}, numOfWalls: 15},
-					}[text.match(/^(ベイビー|スーパー|ハイパー)/)[0]];
-
 					if (!this.state) {
-						const [bo, ans] = await board.getBoard({depth: 1000, ...difficulty});
+						const [bo, ans] = await board.getBoard({depth: 1000, preset: text.match(/^(ベイビー|スーパー|ハイパー)/)[1]});
 						this.state = {
 							board: bo,
 							answer: ans,
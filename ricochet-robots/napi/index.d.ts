@@ -0,0 +1,83 @@
+// Types of the addon built from src/lib.rs (target/release/libricochet_robots_napi.so), loaded by index.js.
+
+export interface JsPos {
+	y: number,
+	x: number,
+}
+
+export interface JsWall {
+	y: number,
+	x: number,
+	d: number,
+}
+
+export interface JsMove {
+	c: number,
+	d: number,
+}
+
+export interface JsGoal {
+	colour: number,
+	y: number,
+	x: number,
+}
+
+export interface JsBoard {
+	h: number,
+	w: number,
+	walls: JsWall[],
+	robots: JsPos[],
+	blocked: JsPos[],
+	holes: JsPos[],
+	torus: boolean,
+	teleporters: JsPos[][],
+	sticky: JsPos[],
+	ice: JsPos[],
+}
+
+export interface JsCommand {
+	moves: JsMove[],
+	isMADE: boolean,
+}
+
+export interface JsProblem {
+	board: JsBoard,
+	goal: JsGoal,
+	answer: JsMove[],
+	goalStrategy: string,
+}
+
+export interface JsEvaluation {
+	optimalLength: number,
+	moves: number,
+	solved: boolean,
+	goalRobot?: JsPos,
+	remaining?: number,
+	firstDeviation?: number,
+}
+
+export interface GenerateOptions {
+	preset?: string,
+	depth?: number,
+	h?: number,
+	w?: number,
+	wallNum?: number,
+	wallPolicy?: string,
+	shape?: string,
+	holeNum?: number,
+	torus?: boolean,
+	teleporterNum?: number,
+	stickyNum?: number,
+	iceNum?: number,
+	minRobots?: number,
+	requireBlocker?: boolean,
+	goalStrategy?: string,
+	seed?: number,
+}
+
+export function generate(options: GenerateOptions): Promise<JsProblem>;
+export function solve(board: JsBoard, goal: JsGoal, maxDepth: number): Promise<JsMove[] | null>;
+export function solveExact(board: JsBoard, goal: JsGoal, length: number): Promise<JsMove[] | null>;
+export function verify(board: JsBoard, goal: JsGoal, maxDepth: number, moves: JsMove[]): Promise<JsEvaluation>;
+export function parseAnswer(text: string): JsCommand;
+export function formatMoves(moves: JsMove[]): string;
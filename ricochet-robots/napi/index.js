@@ -0,0 +1,14 @@
+'use strict';
+
+// Loads the addon built by `cargo build --release -p ricochet-robots-napi`. The types are in index.d.ts.
+const path = require('path');
+
+const fileNames = {
+	darwin: 'libricochet_robots_napi.dylib',
+	win32: 'ricochet_robots_napi.dll',
+};
+const file = path.join(__dirname, '../../target/release', fileNames[process.platform] || 'libricochet_robots_napi.so');
+
+const addon = {exports: {}};
+process.dlopen(addon, file);
+module.exports = addon.exports;
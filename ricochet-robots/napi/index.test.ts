@@ -0,0 +1,43 @@
+import * as addon from './index';
+
+// Needs the addon built by `cargo build --release -p ricochet-robots-napi`.
+
+describe('ricochet robots addon', () => {
+	it('verifies the answer of a generated problem', async () => {
+		const problem = await addon.generate({preset: 'ハイパー', depth: 5, seed: 1});
+		expect(problem.board.h).toBe(7);
+		expect(problem.board.w).toBe(9);
+		expect(problem.goalStrategy).toBe('last');
+
+		const evaluation = await addon.verify(problem.board, problem.goal, 30, problem.answer);
+		expect(evaluation.solved).toBe(true);
+		expect(evaluation.optimalLength).toBe(problem.answer.length);
+
+		const shortened = await addon.verify(problem.board, problem.goal, 30, problem.answer.slice(1));
+		expect(shortened.solved).toBe(false);
+		expect(shortened.optimalLength).toBe(problem.answer.length);
+	});
+
+	it('generates without a preset', async () => {
+		const problem = await addon.generate({h: 3, w: 3, wallNum: 2, depth: 3, seed: 2});
+		const answer = await addon.solve(problem.board, problem.goal, 3);
+		expect(answer).toHaveLength(problem.answer.length);
+	});
+
+	it('generates from seed 0', async () => {
+		const problem = await addon.generate({preset: 'baby', seed: 0});
+		expect(problem.answer.length).toBeGreaterThan(0);
+	});
+
+	it('rejects unknown presets and missing sizes', async () => {
+		await expect(addon.generate({preset: 'nope'})).rejects.toThrow('unknown preset');
+		await expect(addon.generate({h: 3, w: 3, depth: 3})).rejects.toThrow('wallNum is required');
+	});
+
+	it('parses answers as players type them', () => {
+		const command = addon.parseAnswer('赤下右青上まで');
+		expect(command.moves).toHaveLength(3);
+		expect(command.isMADE).toBe(true);
+		expect(addon.formatMoves(command.moves)).toBe('赤下,赤右,青上');
+	});
+});
@@ -0,0 +1,510 @@
+//! Node-API bindings of the ricochet robots engine.
+//! The functions return promises, and the work runs on the thread pool of libuv.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+extern crate napi;
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, Error, Result, Status, Task};
+
+#[macro_use]
+extern crate napi_derive;
+
+extern crate rand;
+
+extern crate ricochet_robots;
+use ricochet_robots::evaluate::{evaluate_answer, AnswerEvaluation};
+use ricochet_robots::notation::{format_moves as format_notation, parse_command};
+use ricochet_robots::pool::BoardRecord;
+use ricochet_robots::preset::{default_presets, find_preset};
+use ricochet_robots::{
+	generate_problem, seed_states, Board, BoardOptions, Move, Pos, WallPos, ROBOTS_COUNT,
+};
+
+#[napi(object)]
+pub struct JsPos {
+	pub y: i32,
+	pub x: i32,
+}
+
+#[napi(object)]
+pub struct JsWall {
+	pub y: i32,
+	pub x: i32,
+	pub d: i32,
+}
+
+#[napi(object)]
+pub struct JsMove {
+	pub c: u32,
+	pub d: u32,
+}
+
+#[napi(object)]
+pub struct JsGoal {
+	pub colour: u32,
+	pub y: i32,
+	pub x: i32,
+}
+
+/// Same as `BoardRecord`.
+#[napi(object)]
+pub struct JsBoard {
+	pub h: u32,
+	pub w: u32,
+	pub walls: Vec<JsWall>,
+	pub robots: Vec<JsPos>,
+	pub blocked: Vec<JsPos>,
+	pub holes: Vec<JsPos>,
+	pub torus: bool,
+	pub teleporters: Vec<Vec<JsPos>>,
+	pub sticky: Vec<JsPos>,
+	pub ice: Vec<JsPos>,
+}
+
+/// Same as `Command`, and as `Command` in board.ts.
+#[napi(object)]
+pub struct JsCommand {
+	pub moves: Vec<JsMove>,
+	#[napi(js_name = "isMADE")]
+	pub made: bool,
+}
+
+#[napi(object)]
+pub struct JsProblem {
+	pub board: JsBoard,
+	pub goal: JsGoal,
+	pub answer: Vec<JsMove>,
+	/// The strategy which picked the goal, written like `goal_strategy`.
+	pub goal_strategy: String,
+}
+
+/// Same as `AnswerEvaluation`.
+#[napi(object)]
+pub struct JsEvaluation {
+	pub optimal_length: u32,
+	pub moves: u32,
+	pub solved: bool,
+	pub goal_robot: Option<JsPos>,
+	pub remaining: Option<u32>,
+	pub first_deviation: Option<u32>,
+}
+
+/// The arguments of the problem generator. The optional ones default to the preset,
+/// or to `BoardOptions::default()` without one.
+#[napi(object)]
+pub struct GenerateOptions {
+	/// A name or alias of a preset in the bundled presets.json, like `ハイパー`.
+	pub preset: Option<String>,
+	/// The target length of the answer, capped at 255. Required without `preset`, like the size.
+	pub depth: Option<u32>,
+	pub h: Option<u32>,
+	pub w: Option<u32>,
+	pub wall_num: Option<u32>,
+	pub wall_policy: Option<String>,
+	pub shape: Option<String>,
+	pub hole_num: Option<u32>,
+	pub torus: Option<bool>,
+	pub teleporter_num: Option<u32>,
+	pub sticky_num: Option<u32>,
+	pub ice_num: Option<u32>,
+	/// Retries until every optimal solution moves this many robots.
+	pub min_robots: Option<u32>,
+	/// Retries until every optimal solution stops the goal robot against another robot.
+	pub require_blocker: Option<bool>,
+	/// `last` (default), `uniform`, `corner` or `robot:<colour>`.
+	pub goal_strategy: Option<String>,
+	/// Seeds the board. Defaults to the current time.
+	pub seed: Option<i64>,
+}
+
+fn invalid_arg(reason: String) -> Error {
+	Error::new(Status::InvalidArg, reason)
+}
+
+fn to_js_pos(p: &Pos) -> JsPos {
+	JsPos {
+		y: p.y.into(),
+		x: p.x.into(),
+	}
+}
+
+fn to_pos(p: &JsPos) -> Result<Pos> {
+	match (p.y as i8, p.x as i8) {
+		(y, x) if i32::from(y) == p.y && i32::from(x) == p.x => Ok(Pos { y, x }),
+		_ => Err(invalid_arg(format!("invalid position ({}, {})", p.y, p.x))),
+	}
+}
+
+fn to_positions(ps: &[JsPos]) -> Result<Vec<Pos>> {
+	ps.iter().map(to_pos).collect()
+}
+
+fn to_js_moves(moves: &[Move]) -> Vec<JsMove> {
+	moves
+		.iter()
+		.map(|m| JsMove {
+			c: m.c as u32,
+			d: m.d as u32,
+		})
+		.collect()
+}
+
+fn to_moves(moves: &[JsMove]) -> Result<Vec<Move>> {
+	moves
+		.iter()
+		.map(|m| {
+			if (m.c as usize) < ROBOTS_COUNT && m.d < 4 {
+				Ok(Move {
+					c: m.c as usize,
+					d: m.d as usize,
+				})
+			} else {
+				Err(invalid_arg(format!("invalid move ({}, {})", m.c, m.d)))
+			}
+		})
+		.collect()
+}
+
+fn to_js_board(rec: BoardRecord) -> JsBoard {
+	let positions = |ps: &[Pos]| ps.iter().map(to_js_pos).collect();
+	JsBoard {
+		h: rec.h as u32,
+		w: rec.w as u32,
+		walls: rec
+			.walls
+			.iter()
+			.map(|wall| JsWall {
+				y: wall.y.into(),
+				x: wall.x.into(),
+				d: wall.d.into(),
+			})
+			.collect(),
+		robots: positions(&rec.robots),
+		blocked: positions(&rec.blocked),
+		holes: positions(&rec.holes),
+		torus: rec.torus,
+		teleporters: rec.teleporters.iter().map(|pads| positions(pads)).collect(),
+		sticky: positions(&rec.sticky),
+		ice: positions(&rec.ice),
+	}
+}
+
+fn to_board(board: &JsBoard) -> Result<Board> {
+	let walls = board
+		.walls
+		.iter()
+		.map(|wall| {
+			let p = to_pos(&JsPos {
+				y: wall.y,
+				x: wall.x,
+			})?;
+			Ok(WallPos {
+				y: p.y,
+				x: p.x,
+				d: wall.d as i8,
+			})
+		})
+		.collect::<Result<_>>()?;
+	let teleporters = board
+		.teleporters
+		.iter()
+		.map(|pads| match &pads[..] {
+			[a, b] => Ok([to_pos(a)?, to_pos(b)?]),
+			_ => Err(invalid_arg(format!(
+				"expected a pair of pads, got {}",
+				pads.len()
+			))),
+		})
+		.collect::<Result<_>>()?;
+	let rec = BoardRecord {
+		h: board.h as usize,
+		w: board.w as usize,
+		walls,
+		robots: to_positions(&board.robots)?,
+		blocked: to_positions(&board.blocked)?,
+		holes: to_positions(&board.holes)?,
+		torus: board.torus,
+		teleporters,
+		sticky: to_positions(&board.sticky)?,
+		ice: to_positions(&board.ice)?,
+	};
+	rec.to_board().map_err(invalid_arg)
+}
+
+fn to_goal(goal: &JsGoal) -> Result<(usize, Pos)> {
+	if goal.colour as usize >= ROBOTS_COUNT {
+		return Err(invalid_arg(format!("invalid colour {}", goal.colour)));
+	}
+	let p = to_pos(&JsPos {
+		y: goal.y,
+		x: goal.x,
+	})?;
+	Ok((goal.colour as usize, p))
+}
+
+/// The board and the target depth, like `BoardSpec` of the command-line tool.
+struct Spec {
+	depth: u8,
+	h: usize,
+	w: usize,
+	wall_num: usize,
+	options: BoardOptions,
+}
+
+fn to_spec(options: &GenerateOptions) -> Result<Spec> {
+	let presets = default_presets();
+	let preset = match &options.preset {
+		Some(name) => Some(
+			find_preset(&presets, name)
+				.ok_or_else(|| invalid_arg(format!("unknown preset {:?}", name)))?,
+		),
+		None => None,
+	};
+	let require = |value: Option<u32>, from_preset: Option<usize>, name: &str| {
+		value
+			.map(|v| v as usize)
+			.or(from_preset)
+			.ok_or_else(|| invalid_arg(format!("{} is required without a preset", name)))
+	};
+	let depth = require(options.depth, preset.map(|p| p.depth.into()), "depth")?;
+	// `bfs` stops once it runs out of states, so 255 moves mean the same as any longer depth.
+	let depth = depth.min(u8::MAX.into()) as u8;
+	let h = require(options.h, preset.map(|p| p.h), "h")?;
+	let w = require(options.w, preset.map(|p| p.w), "w")?;
+	let wall_num = require(options.wall_num, preset.map(|p| p.wall_num), "wallNum")?;
+
+	// The options given override the ones of the preset.
+	let mut res = preset.map_or_else(BoardOptions::default, |p| p.options);
+	if let Some(policy) = &options.wall_policy {
+		res.policy = policy.parse().map_err(invalid_arg)?;
+	}
+	if let Some(shape) = &options.shape {
+		res.shape = shape.parse().map_err(invalid_arg)?;
+	}
+	if let Some(hole_num) = options.hole_num {
+		res.hole_num = hole_num as usize;
+	}
+	res.torus |= options.torus.unwrap_or(false);
+	if let Some(teleporter_num) = options.teleporter_num {
+		res.teleporter_num = teleporter_num as usize;
+	}
+	if let Some(sticky_num) = options.sticky_num {
+		res.sticky_num = sticky_num as usize;
+	}
+	if let Some(ice_num) = options.ice_num {
+		res.ice_num = ice_num as usize;
+	}
+	// A shorter depth than the preset allows asks for that many moves.
+	res.min_length = res.min_length.min(depth as usize);
+	if let Some(min_robots) = options.min_robots {
+		res.min_robots = min_robots as usize;
+	}
+	res.require_blocker |= options.require_blocker.unwrap_or(false);
+	if let Some(goal) = &options.goal_strategy {
+		res.goal = goal.parse().map_err(invalid_arg)?;
+	}
+	res.check(h, w, wall_num).map_err(invalid_arg)?;
+	Ok(Spec {
+		depth,
+		h,
+		w,
+		wall_num,
+		options: res,
+	})
+}
+
+pub struct Generate {
+	options: GenerateOptions,
+}
+
+impl Task for Generate {
+	type Output = JsProblem;
+	type JsValue = JsProblem;
+
+	fn compute(&mut self) -> Result<Self::Output> {
+		let spec = to_spec(&self.options)?;
+		let seed = match self.options.seed {
+			Some(seed) => seed as u64,
+			None => SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.unwrap()
+				.as_nanos() as u64,
+		};
+		let problem = generate_problem(
+			spec.h,
+			spec.w,
+			spec.wall_num,
+			&spec.options,
+			spec.depth,
+			seed_states(seed),
+			&mut rand::thread_rng(),
+		)
+		.map_err(|e| Error::new(Status::GenericFailure, e))?;
+		Ok(JsProblem {
+			board: to_js_board(BoardRecord::new(&problem.board)),
+			goal: JsGoal {
+				colour: problem.goal.0 as u32,
+				y: problem.goal.1.y.into(),
+				x: problem.goal.1.x.into(),
+			},
+			answer: to_js_moves(&problem.answer),
+			goal_strategy: problem.strategy.to_string(),
+		})
+	}
+
+	fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+		Ok(output)
+	}
+}
+
+/// Generates a problem like `ricochet_robot_problem_generator generate`.
+#[napi]
+pub fn generate(options: GenerateOptions) -> AsyncTask<Generate> {
+	AsyncTask::new(Generate { options })
+}
+
+pub struct Solve {
+	board: JsBoard,
+	goal: JsGoal,
+	max_depth: u32,
+}
+
+impl Task for Solve {
+	type Output = Option<Vec<JsMove>>;
+	type JsValue = Option<Vec<JsMove>>;
+
+	fn compute(&mut self) -> Result<Self::Output> {
+		let bo = to_board(&self.board)?;
+		let goal = to_goal(&self.goal)?;
+		Ok(ricochet_robots::solve(&bo, goal, self.max_depth as usize)
+			.map(|moves| to_js_moves(&moves)))
+	}
+
+	fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+		Ok(output)
+	}
+}
+
+/// Finds a shortest answer within `maxDepth` moves, or `null` if there is none.
+#[napi]
+pub fn solve(board: JsBoard, goal: JsGoal, max_depth: u32) -> AsyncTask<Solve> {
+	AsyncTask::new(Solve {
+		board,
+		goal,
+		max_depth,
+	})
+}
+
+pub struct SolveExact {
+	board: JsBoard,
+	goal: JsGoal,
+	length: u32,
+}
+
+impl Task for SolveExact {
+	type Output = Option<Vec<JsMove>>;
+	type JsValue = Option<Vec<JsMove>>;
+
+	fn compute(&mut self) -> Result<Self::Output> {
+		let bo = to_board(&self.board)?;
+		let goal = to_goal(&self.goal)?;
+		Ok(
+			ricochet_robots::solve_exact(&bo, goal, self.length as usize)
+				.map(|moves| to_js_moves(&moves)),
+		)
+	}
+
+	fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+		Ok(output)
+	}
+}
+
+/// Finds an answer of exactly `length` moves, or `null` if there is none.
+#[napi]
+pub fn solve_exact(board: JsBoard, goal: JsGoal, length: u32) -> AsyncTask<SolveExact> {
+	AsyncTask::new(SolveExact {
+		board,
+		goal,
+		length,
+	})
+}
+
+pub struct Verify {
+	board: JsBoard,
+	goal: JsGoal,
+	max_depth: u32,
+	moves: Vec<JsMove>,
+}
+
+impl Task for Verify {
+	type Output = JsEvaluation;
+	type JsValue = JsEvaluation;
+
+	fn compute(&mut self) -> Result<Self::Output> {
+		let bo = to_board(&self.board)?;
+		let goal = to_goal(&self.goal)?;
+		let moves = to_moves(&self.moves)?;
+		let optimal_length = ricochet_robots::solve(&bo, goal, self.max_depth as usize)
+			.ok_or_else(|| {
+				invalid_arg(format!(
+					"the puzzle has no answer within {} moves",
+					self.max_depth
+				))
+			})?
+			.len();
+		let AnswerEvaluation {
+			optimal_length,
+			moves,
+			solved,
+			goal_robot,
+			remaining,
+			first_deviation,
+		} = evaluate_answer(&bo, goal, optimal_length, &moves);
+		Ok(JsEvaluation {
+			optimal_length: optimal_length as u32,
+			moves: moves as u32,
+			solved,
+			goal_robot: goal_robot.as_ref().map(to_js_pos),
+			remaining: remaining.map(|r| r as u32),
+			first_deviation: first_deviation.map(|i| i as u32),
+		})
+	}
+
+	fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+		Ok(output)
+	}
+}
+
+/// Replays a player's answer and reports how close it got, like `evaluate_answer`.
+/// The optimal length is found by the solver, which gives up after `maxDepth` moves.
+#[napi]
+pub fn verify(
+	board: JsBoard,
+	goal: JsGoal,
+	max_depth: u32,
+	moves: Vec<JsMove>,
+) -> AsyncTask<Verify> {
+	AsyncTask::new(Verify {
+		board,
+		goal,
+		max_depth,
+		moves,
+	})
+}
+
+/// Parses an answer as players type it, like `赤下右青上まで`. Throws with the position of the error.
+#[napi]
+pub fn parse_answer(text: String) -> Result<JsCommand> {
+	let command = parse_command(&text).map_err(|e| invalid_arg(e.to_string()))?;
+	Ok(JsCommand {
+		moves: to_js_moves(&command.moves),
+		made: command.made,
+	})
+}
+
+/// Writes the moves like `logstringfy`, e.g. `赤下,青左`.
+#[napi]
+pub fn format_moves(moves: Vec<JsMove>) -> Result<String> {
+	Ok(format_notation(&to_moves(&moves)?))
+}
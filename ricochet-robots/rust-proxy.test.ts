@@ -0,0 +1,23 @@
+import {getBoard} from './board';
+import {get_data} from './rust-proxy';
+
+// Needs the addon built by `cargo build --release -p ricochet-robots-napi`.
+
+describe('rust-proxy', () => {
+	it('generates the boards the bot asks for', async () => {
+		const problem = await get_data({depth: 1000, preset: 'ハイパー'});
+		expect(problem.board.h).toBe(7);
+		expect(problem.board.w).toBe(9);
+		expect(problem.answer.length).toBeGreaterThan(0);
+	}, 60000);
+
+	it('gives an answer which clears the board', async () => {
+		const [bo, answer] = await getBoard({depth: 1000, preset: 'ベイビー'});
+		bo.movecommand(answer);
+		expect(bo.iscleared()).toBe(true);
+	}, 60000);
+
+	it('rejects unknown presets', async () => {
+		await expect(get_data({depth: 1000, preset: 'nope'})).rejects.toThrow('unknown preset');
+	});
+});
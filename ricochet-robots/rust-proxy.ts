@@ -1,24 +1,15 @@
 'use strict';
 
-import child_process from 'child_process';
-import concatstream from 'concat-stream';
-import path from 'path';
+import {generate} from './napi';
+import type {JsProblem} from './napi';
 
 interface BoardSpec {
 	depth: number,
-	size: {h: number, w: number},
-	numOfWalls: number,
+	preset: string,
 }
 
-export const get_data = async (boardspec: BoardSpec) => {
-	const generator = child_process.spawn(
-											path.join(process.cwd(),'target/release/ricochet_robot_problem_generator'),
-											[`${boardspec.depth}`,`${boardspec.size.h}`,`${boardspec.size.w}`,`${boardspec.numOfWalls}`]);
-	const output = await new Promise((resolve) => {
-		const stream = concatstream({ encoding: 'buffer' }, (data) => {
-			resolve(data);
-		});
-		generator.stdout.pipe(stream);
-	});
-	return output.toString();
-};
+// Generates in the addon built by `cargo build --release -p ricochet-robots-napi` (see napi/index.js).
+// It rejects with the message of the generator, such as an unknown preset.
+export const get_data = (boardspec: BoardSpec): Promise<JsProblem> => (
+	generate({preset: boardspec.preset, depth: boardspec.depth})
+);
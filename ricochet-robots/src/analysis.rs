@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+
+extern crate serde_derive;
+use serde_derive::Serialize;
+
+use crate::pool::GoalRecord;
+use crate::{Board, Pos, State, REMOVED, ROBOTS_COUNT};
+
+/// The most cells the command-line tools explore the state space of. 8x8 already takes most of a minute.
+pub const MAX_CELLS: usize = 64;
+
+#[derive(Serialize, Debug)]
+pub struct StateSpaceReport {
+	pub state_count: usize,
+	/// `depth_histogram[d]` is the number of states whose distance from the start is `d`.
+	pub depth_histogram: Vec<usize>,
+	/// The distance to the farthest reachable state.
+	pub eccentricity: usize,
+	/// The optimal length of the hardest robot/cell goals.
+	pub goal_depth: usize,
+	pub farthest_goals: Vec<GoalRecord>,
+	/// The number of robot/cell pairs which can't be reached at all. Blocked cells and holes aren't counted.
+	pub unreachable_goals: usize,
+}
+
+impl StateSpaceReport {
+	/**
+	 * Explores every state reachable from the start position.
+	 * The number of states grows like (h * w)^4, so this is meant for small boards.
+	 */
+	pub fn new(bo: &Board) -> StateSpaceReport {
+		let init = State::init_state(bo);
+		let mut visited: HashSet<State> = HashSet::new();
+		visited.insert(init.clone());
+
+		let mut goal_dist = vec![vec![[None; ROBOTS_COUNT]; bo.w]; bo.h];
+		let mut depth_histogram = vec![];
+		let mut layer = vec![init];
+		while !layer.is_empty() {
+			let depth = depth_histogram.len();
+			depth_histogram.push(layer.len());
+			let mut next = vec![];
+			for st in &layer {
+				for (i, p) in st.robots.iter().enumerate() {
+					if *p == REMOVED {
+						continue;
+					}
+					let d = &mut goal_dist[p.y as usize][p.x as usize][i];
+					if d.is_none() {
+						*d = Some(depth);
+					}
+				}
+				for (ts, _) in st.enumerate_states(bo) {
+					if !visited.contains(&ts) {
+						visited.insert(ts.clone());
+						next.push(ts);
+					}
+				}
+			}
+			layer = next;
+		}
+
+		let mut goal_depth = 0;
+		let mut farthest_goals = vec![];
+		let mut unreachable_goals = 0;
+		for (y, row) in goal_dist.iter().enumerate() {
+			for (x, cell) in row.iter().enumerate() {
+				if bo.is_blocked(y, x)
+					|| bo.is_hole(Pos {
+						y: y as i8,
+						x: x as i8,
+					}) {
+					continue;
+				}
+				for (colour, d) in cell.iter().enumerate() {
+					let d = match d {
+						Some(d) => *d,
+						None => {
+							unreachable_goals += 1;
+							continue;
+						}
+					};
+					if d > goal_depth {
+						goal_depth = d;
+						farthest_goals.clear();
+					}
+					if d == goal_depth {
+						farthest_goals.push(GoalRecord {
+							colour,
+							y: y as i8,
+							x: x as i8,
+						});
+					}
+				}
+			}
+		}
+
+		StateSpaceReport {
+			state_count: visited.len(),
+			eccentricity: depth_histogram.len() - 1,
+			depth_histogram,
+			goal_depth,
+			farthest_goals,
+			unreachable_goals,
+		}
+	}
+}
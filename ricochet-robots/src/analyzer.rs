@@ -0,0 +1,77 @@
+use std::env;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+extern crate xorshift;
+use xorshift::{Rng, SeedableRng, Xorshift128};
+
+extern crate ricochet_robots;
+use ricochet_robots::analysis::{StateSpaceReport, MAX_CELLS};
+use ricochet_robots::pool::BoardRecord;
+use ricochet_robots::{Board, BoardOptions};
+
+extern crate serde_derive;
+use serde_derive::Serialize;
+
+extern crate serde_json;
+
+#[derive(Serialize)]
+struct AnalysisRecord {
+	board: BoardRecord,
+	analysis: StateSpaceReport,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
+	let args: Vec<String> = env::args().collect();
+	if args.len() < 4 {
+		return Err(format!(
+			"invalid argument. expect \"board_h board_w wall_num [count]\", got {:?}.",
+			&args[1..]
+		)
+		.into());
+	}
+	let board_h: usize = args[1].parse()?;
+	let board_w: usize = args[2].parse()?;
+	let wall_num: usize = args[3].parse()?;
+	let count: usize = match args.get(4) {
+		Some(count) => count.parse()?,
+		None => 1,
+	};
+	let checked = BoardOptions::default()
+		.check(board_h, board_w, wall_num)
+		.and_then(|()| {
+			if board_h * board_w > MAX_CELLS {
+				Err(format!(
+					"can't analyze boards of more than {} cells",
+					MAX_CELLS
+				))
+			} else {
+				Ok(())
+			}
+		});
+	if let Err(e) = checked {
+		eprintln!("error: {}", e);
+		process::exit(2);
+	}
+
+	let now = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap()
+		.as_nanos() as u64;
+	let mut rng: Xorshift128 = SeedableRng::from_seed(&[now, now][..]);
+	for _ in 0..count {
+		let states: [u64; 2] = [rng.gen(), rng.gen()];
+		let bo = Board::new(
+			board_h,
+			board_w,
+			SeedableRng::from_seed(&states[..]),
+			wall_num,
+		);
+		let record = AnalysisRecord {
+			analysis: StateSpaceReport::new(&bo),
+			board: BoardRecord::new(&bo),
+		};
+		println!("{}", serde_json::to_string(&record)?);
+	}
+	Ok(())
+}
@@ -1,3 +1,6 @@
+//! Checks `bfs` against bench_correctness_check.txt and prints rough timings of the solvers.
+//! Timings with statistics and saved baselines are in benches/engine.rs.
+
 use std::fs;
 
 extern crate xorshift;
@@ -5,8 +8,8 @@ use xorshift::{Rng, SeedableRng, Xorshift128};
 
 use std::time::Instant;
 
-#[allow(dead_code)]
-mod main;
+extern crate ricochet_robots;
+use ricochet_robots as main;
 
 fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
 	let board_h = 5;
@@ -31,12 +34,38 @@ fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
 
 	let start = Instant::now();
 	for bo in &bos[1..] {
-		main::bfs(100, &bo);
+		main::bfs(100, bo);
 	}
 	let end = start.elapsed();
 
-	println!("{}.{:03}s", end.as_secs(), end.subsec_nanos() / 1_000_000);
+	println!("{}.{:03}s", end.as_secs(), end.subsec_millis());
+
+	// Targeted solving of the hardest goal of each board, from one end and from both ends.
+	let goals: Vec<_> = bos[1..].iter().map(|bo| main::bfs(100, bo).0).collect();
+	let start = Instant::now();
+	let forward: Vec<_> = bos[1..]
+		.iter()
+		.zip(&goals)
+		.map(|(bo, &goal)| main::solve(bo, goal, 100))
+		.collect();
+	let forward_time = start.elapsed();
+	let start = Instant::now();
+	let bidirectional: Vec<_> = bos[1..]
+		.iter()
+		.zip(&goals)
+		.map(|(bo, &goal)| main::bidirectional::solve_bidirectional(bo, goal, 100))
+		.collect();
+	let bidirectional_time = start.elapsed();
+	for (f, b) in forward.iter().zip(&bidirectional) {
+		assert_eq!(f.as_ref().map(Vec::len), b.as_ref().map(Vec::len));
+	}
+
+	println!(
+		"solve: {}.{:03}s, solve_bidirectional: {}.{:03}s",
+		forward_time.as_secs(),
+		forward_time.subsec_millis(),
+		bidirectional_time.as_secs(),
+		bidirectional_time.subsec_millis()
+	);
 	Ok(())
 }
-
-//0.84sec
@@ -0,0 +1,222 @@
+use std::collections::HashMap;
+
+use crate::{Board, Move, Pos, Prev, State, DIRECTIONS, REMOVED, ROBOTS_COUNT};
+
+/// A visited state with the move linking it towards the start (or the goal), and its distance from there.
+/// The move is meaningless at distance 0.
+type Visited = HashMap<State, (Prev, usize)>;
+
+/**
+ * Same as `solve`, but searches from both ends and meets in the middle.
+ * The backward search starts from every state with the goal robot on the goal,
+ * so it starts with about (h * w)^3 states and is meant for deep goals on small boards.
+ */
+pub fn solve_bidirectional(bo: &Board, goal: (usize, Pos), max_depth: usize) -> Option<Vec<Move>> {
+	let init = State::init_state(bo);
+	if init.robots[goal.0] == goal.1 {
+		return Some(vec![]);
+	}
+
+	// Forward entries hold the move into the state and the robot's position before it,
+	// backward entries the move out of the state and the robot's position after it.
+	let mut forward: Visited = HashMap::new();
+	forward.insert(init.clone(), (Prev::dummy(), 0));
+	let mut forward_layer = vec![init];
+	let mut backward: Visited = HashMap::new();
+	let mut backward_layer = goal_states(bo, goal);
+	for st in &backward_layer {
+		backward.insert(st.clone(), (Prev::dummy(), 0));
+	}
+	let (mut forward_depth, mut backward_depth) = (0, 0);
+
+	while forward_depth + backward_depth < max_depth {
+		// The shortest path through the states met in this layer, as (length, state).
+		let mut best: Option<(usize, State)> = None;
+		if forward_layer.len() <= backward_layer.len() {
+			let mut next = vec![];
+			for st in &forward_layer {
+				for (ts, m) in st.enumerate_states(bo) {
+					if forward.contains_key(&ts) {
+						continue;
+					}
+					forward.insert(
+						ts.clone(),
+						(Prev::serialize(&m, &st.robots[m.c]), forward_depth + 1),
+					);
+					if let Some(&(_, depth)) = backward.get(&ts) {
+						let length = forward_depth + 1 + depth;
+						if !matches!(best, Some((l, _)) if l <= length) {
+							best = Some((length, ts.clone()));
+						}
+					}
+					next.push(ts);
+				}
+			}
+			forward_layer = next;
+			forward_depth += 1;
+		} else {
+			let mut next = vec![];
+			for st in &backward_layer {
+				for (ps, m) in predecessors(bo, st) {
+					if backward.contains_key(&ps) {
+						continue;
+					}
+					backward.insert(
+						ps.clone(),
+						(Prev::serialize(&m, &st.robots[m.c]), backward_depth + 1),
+					);
+					if let Some(&(_, depth)) = forward.get(&ps) {
+						let length = backward_depth + 1 + depth;
+						if !matches!(best, Some((l, _)) if l <= length) {
+							best = Some((length, ps.clone()));
+						}
+					}
+					next.push(ps);
+				}
+			}
+			backward_layer = next;
+			backward_depth += 1;
+		}
+
+		// A shorter path would have met in an earlier layer, so the best one here is the shortest.
+		if let Some((_, meet)) = best {
+			return Some(join_paths(&forward, &backward, meet));
+		}
+		if forward_layer.is_empty() || backward_layer.is_empty() {
+			return None;
+		}
+	}
+	None
+}
+
+/// Joins the path from the start to `meet` and the path from `meet` to the goal.
+fn join_paths(forward: &Visited, backward: &Visited, meet: State) -> Vec<Move> {
+	let mut l = vec![];
+	let mut s = meet.clone();
+	while forward[&s].1 > 0 {
+		let (m, p) = forward[&s].0.deserialize();
+		l.push(m);
+		s.robots[m.c] = p;
+	}
+	l.reverse();
+	let mut s = meet;
+	while backward[&s].1 > 0 {
+		let (m, p) = backward[&s].0.deserialize();
+		l.push(m);
+		s.robots[m.c] = p;
+	}
+	l
+}
+
+/// Every state with the goal robot on the goal and the other robots anywhere else they can stand.
+fn goal_states(bo: &Board, goal: (usize, Pos)) -> Vec<State> {
+	let mut cells: Vec<Pos> = (0..bo.h)
+		.flat_map(|y| (0..bo.w).map(move |x| (y, x)))
+		.filter(|&(y, x)| !bo.is_blocked(y, x))
+		.map(|(y, x)| Pos {
+			y: y as i8,
+			x: x as i8,
+		})
+		.filter(|&p| !bo.is_hole(p))
+		.collect();
+	if !cells.contains(&goal.1) {
+		return vec![];
+	}
+	// Robots which fell into a hole end the game anywhere.
+	if !bo.holes.is_empty() {
+		cells.push(REMOVED);
+	}
+
+	let mut res = vec![];
+	let mut robots = [REMOVED; ROBOTS_COUNT];
+	robots[goal.0] = goal.1;
+	fn place(
+		i: usize,
+		goal_robot: usize,
+		cells: &[Pos],
+		robots: &mut [Pos; ROBOTS_COUNT],
+		res: &mut Vec<State>,
+	) {
+		if i == ROBOTS_COUNT {
+			res.push(State { robots: *robots });
+			return;
+		}
+		if i == goal_robot {
+			place(i + 1, goal_robot, cells, robots, res);
+			return;
+		}
+		for &p in cells {
+			if p == robots[goal_robot] || (p != REMOVED && robots[..i].contains(&p)) {
+				continue;
+			}
+			robots[i] = p;
+			place(i + 1, goal_robot, cells, robots, res);
+		}
+	}
+	place(0, goal.0, &cells, &mut robots, &mut res);
+	res
+}
+
+/**
+ * The states from which a single move leads to `st`, with those moves.
+ * A robot slid to its cell only if the cell stops it, so only the cells behind it are tried,
+ * and each candidate is checked by moving it forward.
+ * With teleporters, or for a robot which fell into a hole, every cell is tried.
+ */
+fn predecessors(bo: &Board, st: &State) -> Vec<(State, Move)> {
+	let mut res = vec![];
+	let all_cells = || {
+		(0..bo.h).flat_map(move |y| {
+			(0..bo.w).map(move |x| Pos {
+				y: y as i8,
+				x: x as i8,
+			})
+		})
+	};
+	for c in 0..ROBOTS_COUNT {
+		let p = st.robots[c];
+		for (d, dir) in DIRECTIONS.iter().enumerate() {
+			let mut candidates: Vec<Pos> = vec![];
+			if p == REMOVED || !bo.teleporters.is_empty() {
+				candidates.extend(all_cells());
+			} else {
+				let len = if dir.x == 0 { bo.h } else { bo.w };
+				let mut q = p;
+				for _ in 1..len {
+					let (y, x) = (q.y - dir.y, q.x - dir.x);
+					let on_board = 0 <= y && y < bo.h as i8 && 0 <= x && x < bo.w as i8;
+					if !on_board && !bo.torus {
+						break;
+					}
+					q = Pos {
+						y: y.rem_euclid(bo.h as i8),
+						x: x.rem_euclid(bo.w as i8),
+					};
+					// A wall between `q` and the cell ahead, or a robot, stops everything from further back.
+					if bo.walldist[q.y as usize][q.x as usize][d] == 0 || st.robots.contains(&q) {
+						break;
+					}
+					candidates.push(q);
+					if bo.is_sticky(q) {
+						break;
+					}
+				}
+			}
+			for q in candidates {
+				if q == p
+					|| bo.is_blocked(q.y as usize, q.x as usize)
+					|| bo.is_hole(q)
+					|| st.robots.contains(&q)
+				{
+					continue;
+				}
+				let mut ps = st.clone();
+				ps.robots[c] = q;
+				if ps.move_to(bo, c, d).as_ref() == Some(st) {
+					res.push((ps, Move { c, d }));
+				}
+			}
+		}
+	}
+	res
+}
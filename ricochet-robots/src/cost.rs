@@ -0,0 +1,205 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::str::FromStr;
+
+extern crate serde_derive;
+use serde_derive::Serialize;
+
+use crate::{Board, Move, Pos, State};
+
+/// What `solve_with_cost` minimises.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CostModel {
+	/// Every move costs 1, like `bfs`.
+	Moves,
+	/// Each selection of a robot costs 1. Moving the same robot again is free.
+	RobotSelections,
+	/// Moves of the goal robot cost `goal`, moves of the other robots cost `other`.
+	Weighted { goal: u32, other: u32 },
+}
+
+impl FromStr for CostModel {
+	type Err = String;
+
+	/// Parses `moves`, `selections` or `weighted:<goal>:<other>`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let args: Vec<&str> = s.split(':').collect();
+		match args[..] {
+			["moves"] => Ok(CostModel::Moves),
+			["selections"] => Ok(CostModel::RobotSelections),
+			["weighted", goal, other] => match (goal.parse(), other.parse()) {
+				(Ok(goal), Ok(other)) => Ok(CostModel::Weighted { goal, other }),
+				_ => Err(format!("invalid weights {:?}", s)),
+			},
+			_ => Err(format!("unknown cost model {:?}", s)),
+		}
+	}
+}
+
+impl CostModel {
+	/// The cost of `m` right after a move of the robot `last` (`None` for the first move).
+	fn edge_cost(&self, last: Option<usize>, m: &Move, goal_robot: usize) -> u32 {
+		match *self {
+			CostModel::Moves => 1,
+			CostModel::RobotSelections => {
+				if last == Some(m.c) {
+					0
+				} else {
+					1
+				}
+			}
+			CostModel::Weighted { goal, other } => {
+				if m.c == goal_robot {
+					goal
+				} else {
+					other
+				}
+			}
+		}
+	}
+
+	/// Whether the cost of a move depends on the robot moved before it.
+	fn depends_on_last(&self) -> bool {
+		*self == CostModel::RobotSelections
+	}
+
+	/// Whether every edge costs 0 or 1, so that a deque is enough.
+	fn is_zero_one(&self) -> bool {
+		match *self {
+			CostModel::Moves | CostModel::RobotSelections => true,
+			CostModel::Weighted { goal, other } => goal <= 1 && other <= 1,
+		}
+	}
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CostReport {
+	pub cost: u32,
+	pub moves: usize,
+	/// The number of runs of consecutive moves by the same robot.
+	pub robot_selections: usize,
+	pub goal_moves: usize,
+	pub other_moves: usize,
+	/// The moves in the playing order.
+	pub answer: Vec<Move>,
+}
+
+impl CostReport {
+	fn new(answer: Vec<Move>, goal_robot: usize, model: &CostModel) -> CostReport {
+		let mut cost = 0;
+		let mut last = None;
+		let mut robot_selections = 0;
+		for m in &answer {
+			cost += model.edge_cost(last, m, goal_robot);
+			if last != Some(m.c) {
+				robot_selections += 1;
+			}
+			last = Some(m.c);
+		}
+		let goal_moves = answer.iter().filter(|m| m.c == goal_robot).count();
+		CostReport {
+			cost,
+			moves: answer.len(),
+			robot_selections,
+			goal_moves,
+			other_moves: answer.len() - goal_moves,
+			answer,
+		}
+	}
+}
+
+/// A state together with the robot moved last, if the cost of the next move depends on it.
+type Node = (State, Option<usize>);
+
+/// The queue of the search. Both variants hand out the nodes in the order of their costs.
+enum Frontier {
+	ZeroOne(VecDeque<(u32, usize)>),
+	Dijkstra(BinaryHeap<Reverse<(u32, usize)>>),
+}
+
+impl Frontier {
+	fn push(&mut self, cost: u32, edge: u32, node: usize) {
+		match self {
+			Frontier::ZeroOne(deque) if edge == 0 => deque.push_front((cost, node)),
+			Frontier::ZeroOne(deque) => deque.push_back((cost, node)),
+			Frontier::Dijkstra(heap) => heap.push(Reverse((cost, node))),
+		}
+	}
+
+	fn pop(&mut self) -> Option<(u32, usize)> {
+		match self {
+			Frontier::ZeroOne(deque) => deque.pop_front(),
+			Frontier::Dijkstra(heap) => heap.pop().map(|Reverse(e)| e),
+		}
+	}
+}
+
+/**
+ * Finds a cheapest move sequence under `model` that brings the robot `goal.0` onto `goal.1`.
+ * Uses 0-1 BFS when every move costs 0 or 1, and Dijkstra's algorithm otherwise.
+ * Returns `None` if the cost exceeds `max_cost`.
+ */
+pub fn solve_with_cost(
+	bo: &Board,
+	goal: (usize, Pos),
+	model: &CostModel,
+	max_cost: u32,
+) -> Option<CostReport> {
+	// The nodes are numbered in the order of discovery, and `prev` refers to them by number.
+	let mut nodes: Vec<Node> = vec![(State::init_state(bo), None)];
+	let mut index: HashMap<Node, usize> = HashMap::new();
+	index.insert(nodes[0].clone(), 0);
+	let mut dist: Vec<u32> = vec![0];
+	let mut prev: Vec<Option<(usize, Move)>> = vec![None];
+
+	let mut frontier = if model.is_zero_one() {
+		Frontier::ZeroOne(VecDeque::new())
+	} else {
+		Frontier::Dijkstra(BinaryHeap::new())
+	};
+	frontier.push(0, 0, 0);
+
+	let mut last = None;
+	while let Some((cost, i)) = frontier.pop() {
+		if dist[i] < cost {
+			continue;
+		}
+		let (st, last_robot) = nodes[i].clone();
+		if st.robots[goal.0] == goal.1 {
+			last = Some(i);
+			break;
+		}
+		for (ts, m) in st.enumerate_states(bo) {
+			let edge = model.edge_cost(last_robot, &m, goal.0);
+			// Large weights may overflow, and such a sum is over any `max_cost` anyway.
+			let tcost = match cost.checked_add(edge) {
+				Some(tcost) if tcost <= max_cost => tcost,
+				_ => continue,
+			};
+			let tnode = (ts, Some(m.c).filter(|_| model.depends_on_last()));
+			let j = match index.get(&tnode) {
+				Some(&j) if dist[j] <= tcost => continue,
+				Some(&j) => j,
+				None => {
+					index.insert(tnode.clone(), nodes.len());
+					nodes.push(tnode);
+					dist.push(tcost);
+					prev.push(None);
+					nodes.len() - 1
+				}
+			};
+			dist[j] = tcost;
+			prev[j] = Some((i, m));
+			frontier.push(tcost, edge, j);
+		}
+	}
+
+	let mut i = last?;
+	let mut answer = vec![];
+	while let Some((p, m)) = prev[i] {
+		answer.push(m);
+		i = p;
+	}
+	answer.reverse();
+	Some(CostReport::new(answer, goal.0, model))
+}
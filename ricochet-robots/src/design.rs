@@ -0,0 +1,186 @@
+use xorshift::Rng;
+
+use crate::policy::{is_inner, same_wall};
+use crate::{bfs, Board, BoardRng, Move, Pos, WallPos, NO_WALL, ROBOTS_COUNT};
+
+/// Options of `optimise_layout`.
+#[derive(Debug, Clone, Copy)]
+pub struct DesignOptions {
+	/// The number of changes to try.
+	pub iterations: usize,
+	/// The depth given to `bfs`. The search stops as soon as a puzzle this long is found.
+	pub target: u8,
+	/// The temperature of simulated annealing at the start. It cools down linearly to 0.
+	/// With 0, only changes which don't shorten the puzzle are kept (hill climbing).
+	pub temperature: f64,
+	pub min_walls: usize,
+	pub max_walls: usize,
+}
+
+impl Default for DesignOptions {
+	fn default() -> Self {
+		DesignOptions {
+			iterations: 1000,
+			target: 30,
+			temperature: 1.0,
+			min_walls: 0,
+			max_walls: usize::MAX,
+		}
+	}
+}
+
+/// The deepest puzzle found by `optimise_layout`.
+#[derive(Debug)]
+pub struct Design {
+	pub board: Board,
+	pub goal: (usize, Pos),
+	/// The optimal moves in the playing order.
+	pub answer: Vec<Move>,
+	/// The number of changed boards which were good and got solved.
+	pub evaluated: usize,
+}
+
+/**
+ * Improves the board by local search so that the hardest goal `bfs` finds gets as deep as possible.
+ * Each step adds, removes or moves a wall, or moves a robot to another start,
+ * and changes which make the board bad (see `Board::good_board`) are dropped.
+ * Holes, teleporters and terrain stay where they are.
+ */
+pub fn optimise_layout(bo: Board, options: &DesignOptions, rng: &mut BoardRng) -> Design {
+	let (goal, log) = bfs(options.target, &bo);
+	let mut current_len = log.len();
+	let mut current = bo.clone();
+	let mut best = Design {
+		board: bo,
+		goal,
+		answer: log,
+		evaluated: 0,
+	};
+
+	for i in 0..options.iterations {
+		if best.answer.len() >= options.target as usize {
+			break;
+		}
+		let candidate = match change(&current, options, rng) {
+			Some(candidate) => candidate,
+			None => continue,
+		};
+		best.evaluated += 1;
+		let (goal, log) = bfs(options.target, &candidate);
+
+		let temperature = options.temperature * (1.0 - i as f64 / options.iterations as f64);
+		let delta = log.len() as f64 - current_len as f64;
+		let accept =
+			delta >= 0.0 || (temperature > 0.0 && rng.gen::<f64>() < (delta / temperature).exp());
+		if !accept {
+			continue;
+		}
+		current_len = log.len();
+		if log.len() > best.answer.len() {
+			best.board = candidate.clone();
+			best.goal = goal;
+			best.answer = log;
+		}
+		current = candidate;
+	}
+
+	best.answer.reverse();
+	best
+}
+
+/// A random neighbour of the board, or `None` if the change isn't possible or makes the board bad.
+fn change(bo: &Board, options: &DesignOptions, rng: &mut BoardRng) -> Option<Board> {
+	let mut res = bo.clone();
+	match rng.gen_range(0, 4) {
+		0 => {
+			if res.walls.len() >= options.max_walls {
+				return None;
+			}
+			let d = rng.gen_range(0, 2);
+			let wall = WallPos {
+				y: rng.gen_range(0, bo.h + 1 - d) as i8,
+				x: rng.gen_range(0, bo.w + d) as i8,
+				d: d as i8,
+			};
+			if !can_put(&res, &wall) {
+				return None;
+			}
+			res.walls.push(wall);
+		}
+		1 => {
+			if res.walls.is_empty() || res.walls.len() <= options.min_walls {
+				return None;
+			}
+			let i = rng.gen_range(0, res.walls.len());
+			res.walls.swap_remove(i);
+		}
+		2 => {
+			if res.walls.is_empty() {
+				return None;
+			}
+			// Slides the wall by a cell, or turns it around the corner.
+			let i = rng.gen_range(0, res.walls.len());
+			let old = res.walls.swap_remove(i);
+			let d = rng.gen_range(0, 2) as i8;
+			let wall = WallPos {
+				y: old.y + rng.gen_range(0, 3) as i8 - 1,
+				x: old.x + rng.gen_range(0, 3) as i8 - 1,
+				d,
+			};
+			if wall.y < 0 || wall.x < 0 || !can_put(&res, &wall) {
+				return None;
+			}
+			res.walls.push(wall);
+		}
+		_ => {
+			let c = rng.gen_range(0, ROBOTS_COUNT);
+			let p = Pos {
+				y: rng.gen_range(0, bo.h) as i8,
+				x: rng.gen_range(0, bo.w) as i8,
+			};
+			if !can_start_on(&res, p) {
+				return None;
+			}
+			res.robots[c] = p;
+			return Some(res);
+		}
+	}
+	res.rebuild_walldist();
+	if res.good_board() && !has_open_loop(&res) {
+		Some(res)
+	} else {
+		None
+	}
+}
+
+/// Whether the wall separates two cells of the board and isn't there yet.
+fn can_put(bo: &Board, wall: &WallPos) -> bool {
+	// On torus boards, the walls on the top (or left) border also separate the opposite edges.
+	let on_board = if bo.torus {
+		(wall.y as usize) < bo.h && (wall.x as usize) < bo.w
+	} else {
+		is_inner(bo, wall)
+			&& (if wall.d == 0 {
+				(wall.x as usize) < bo.w
+			} else {
+				(wall.y as usize) < bo.h
+			})
+	};
+	on_board && !bo.walls.iter().any(|v| same_wall(v, wall))
+}
+
+/// Whether a robot can start on the cell, like the robots placed by `Board::init`.
+fn can_start_on(bo: &Board, p: Pos) -> bool {
+	!bo.is_blocked(p.y as usize, p.x as usize)
+		&& !bo.is_hole(p)
+		&& bo.teleporter_partner(p).is_none()
+		&& !bo.is_sticky(p)
+		&& !bo.ice.contains(&p)
+		&& !bo.robots.contains(&p)
+}
+
+/// Whether a robot would slide forever in some row or column of a torus board (see `Board::close_loops`).
+fn has_open_loop(bo: &Board) -> bool {
+	(0..bo.h).any(|y| bo.walldist[y][0][1] == NO_WALL)
+		|| (0..bo.w).any(|x| bo.walldist[0][x][0] == NO_WALL)
+}
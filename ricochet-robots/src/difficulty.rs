@@ -0,0 +1,177 @@
+use std::collections::{HashMap, HashSet};
+
+extern crate serde_derive;
+use serde_derive::Serialize;
+
+use crate::{layered_bfs, Board, Move, Pos, State, DIRECTIONS, REMOVED, ROBOTS_COUNT};
+
+/// Weights of the terms in `DifficultyReport::score`.
+#[derive(Debug, Clone, Copy)]
+pub struct DifficultyWeights {
+	pub length: f64,
+	pub robots_moved: f64,
+	pub blocker_moves: f64,
+	pub solution_count: f64,
+	pub frontier_size: f64,
+}
+
+impl Default for DifficultyWeights {
+	fn default() -> Self {
+		DifficultyWeights {
+			length: 1.0,
+			robots_moved: 1.5,
+			blocker_moves: 1.0,
+			solution_count: 0.5,
+			frontier_size: 0.25,
+		}
+	}
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DifficultyReport {
+	pub optimal_length: usize,
+	/// The fewest distinct robots any optimal solution moves.
+	pub robots_moved: usize,
+	/// The fewest moves of robots other than the goal robot in any optimal solution.
+	/// These moves only serve to place blockers.
+	pub blocker_moves: usize,
+	/// The fewest moves of the goal robot which stop against another robot in any optimal solution.
+	pub blocker_stops: usize,
+	pub solution_count: u64,
+	/// The number of states first reached at the optimal depth.
+	pub frontier_size: usize,
+	pub score: f64,
+}
+
+impl DifficultyReport {
+	/**
+	 * Analyses the puzzle of bringing the robot `goal.0` onto `goal.1`,
+	 * assuming that `length` is the optimal number of moves (as returned by `bfs`).
+	 */
+	pub fn new(
+		bo: &Board,
+		goal: (usize, Pos),
+		length: usize,
+		weights: &DifficultyWeights,
+	) -> DifficultyReport {
+		let layers = layered_bfs(bo, length);
+		let is_goal = |st: &State| st.robots[goal.0] == goal.1;
+
+		let solution_count = layers[length]
+			.iter()
+			.filter(|(st, _)| is_goal(st))
+			.fold(0u64, |acc, (_, cnt)| acc.saturating_add(*cnt));
+		let frontier_size = layers[length].len();
+
+		// Keep only the states lying on some optimal solution.
+		let mut on_path: Vec<HashSet<State>> = vec![HashSet::new(); length + 1];
+		on_path[length] = layers[length]
+			.keys()
+			.filter(|st| is_goal(st))
+			.cloned()
+			.collect();
+		for depth in (0..length).rev() {
+			let (lower, upper) = on_path.split_at_mut(depth + 1);
+			for st in layers[depth].keys() {
+				if st
+					.enumerate_states(bo)
+					.iter()
+					.any(|(ts, _)| upper[0].contains(ts))
+				{
+					lower[depth].insert(st.clone());
+				}
+			}
+		}
+
+		// For each state, the sets of robots moved on the way (as a bitset over the 2^ROBOTS_COUNT
+		// subsets) and the fewest blocker moves so far.
+		let mut robot_sets: HashMap<State, u32> = HashMap::new();
+		let mut blockers: HashMap<State, usize> = HashMap::new();
+		let mut stops: HashMap<State, usize> = HashMap::new();
+		for st in &on_path[0] {
+			robot_sets.insert(st.clone(), 1);
+			blockers.insert(st.clone(), 0);
+			stops.insert(st.clone(), 0);
+		}
+		for depth in 0..length {
+			for st in &on_path[depth] {
+				let sets = robot_sets[st];
+				let blocker = blockers[st];
+				let stop = stops[st];
+				for (ts, m) in st.enumerate_states(bo) {
+					if !on_path[depth + 1].contains(&ts) {
+						continue;
+					}
+					let mut moved = 0;
+					for mask in 0..(1 << ROBOTS_COUNT) {
+						if (sets >> mask) & 1 == 1 {
+							moved |= 1 << (mask | (1 << m.c));
+						}
+					}
+					*robot_sets.entry(ts.clone()).or_insert(0) |= moved;
+					let b = blocker + if m.c == goal.0 { 0 } else { 1 };
+					let e = blockers.entry(ts.clone()).or_insert(b);
+					*e = (*e).min(b);
+					let s = stop + (m.c == goal.0 && stopped_by_robot(bo, &ts, &m)) as usize;
+					let e = stops.entry(ts).or_insert(s);
+					*e = (*e).min(s);
+				}
+			}
+		}
+
+		let (robots_moved, blocker_moves, blocker_stops) = on_path[length]
+			.iter()
+			.map(|st| {
+				let sets = robot_sets[st];
+				let robots = (0..(1u32 << ROBOTS_COUNT))
+					.filter(|mask| (sets >> mask) & 1 == 1)
+					.map(|mask| mask.count_ones() as usize)
+					.min()
+					.unwrap_or(0);
+				(robots, blockers[st], stops[st])
+			})
+			.fold((ROBOTS_COUNT, length, length), |(r, b, s), (tr, tb, ts)| {
+				(r.min(tr), b.min(tb), s.min(ts))
+			});
+
+		let mut report = DifficultyReport {
+			optimal_length: length,
+			robots_moved,
+			blocker_moves,
+			blocker_stops,
+			solution_count,
+			frontier_size,
+			score: 0.0,
+		};
+		report.score = report.combined_score(weights);
+		report
+	}
+
+	/**
+	 * Deep puzzles with many robots involved and few solutions score higher.
+	 * Solution count and frontier size span orders of magnitude, so they are taken in log scale.
+	 */
+	pub fn combined_score(&self, weights: &DifficultyWeights) -> f64 {
+		weights.length * self.optimal_length as f64
+			+ weights.robots_moved * self.robots_moved.saturating_sub(1) as f64
+			+ weights.blocker_moves * self.blocker_moves as f64
+			- weights.solution_count * (self.solution_count.max(1) as f64).log2()
+			+ weights.frontier_size * (self.frontier_size.max(1) as f64).log2()
+	}
+}
+
+/// Whether the robot moved by `m` into the state `st` stopped because another robot was right ahead.
+fn stopped_by_robot(bo: &Board, st: &State, m: &Move) -> bool {
+	let p = st.robots[m.c];
+	if p == REMOVED || bo.is_sticky(p) || bo.walldist[p.y as usize][p.x as usize][m.d] == 0 {
+		return false;
+	}
+	let ahead = Pos {
+		y: (p.y + DIRECTIONS[m.d].y).rem_euclid(bo.h as i8),
+		x: (p.x + DIRECTIONS[m.d].x).rem_euclid(bo.w as i8),
+	};
+	st.robots
+		.iter()
+		.enumerate()
+		.any(|(i, &q)| i != m.c && q == ahead)
+}
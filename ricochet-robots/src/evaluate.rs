@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+
+extern crate serde_derive;
+use serde_derive::Serialize;
+
+use crate::{layered_bfs, solve_from, Board, Move, Pos, State, REMOVED};
+
+/// How close a player's answer got to the goal.
+#[derive(Serialize, Debug)]
+pub struct AnswerEvaluation {
+	pub optimal_length: usize,
+	pub moves: usize,
+	pub solved: bool,
+	/// Where the goal robot ended. `None` if it fell into a hole.
+	pub goal_robot: Option<Pos>,
+	/// The fewest moves needed to bring the goal robot home from the final state.
+	/// `None` if it can't get there any more, or needs more than `optimal_length + moves`.
+	pub remaining: Option<usize>,
+	/// The index of the first move after which no optimal solution is possible any more.
+	/// `None` if the answer stays on an optimal line throughout.
+	pub first_deviation: Option<usize>,
+}
+
+/**
+ * Replays `moves` and compares them with the optimal solutions of length `optimal_length`.
+ * Like the bot, a move of a robot which can't go that way leaves the board as it is.
+ * The remaining moves are searched up to `optimal_length + moves.len()`.
+ */
+pub fn evaluate_answer(
+	bo: &Board,
+	goal: (usize, Pos),
+	optimal_length: usize,
+	moves: &[Move],
+) -> AnswerEvaluation {
+	let on_line = optimal_states(bo, goal, optimal_length);
+	let mut st = State::init_state(bo);
+	let mut first_deviation = None;
+	for (i, m) in moves.iter().enumerate() {
+		if let Some(ts) = st.move_to(bo, m.c, m.d) {
+			st = ts;
+		}
+		if first_deviation.is_none()
+			&& !on_line
+				.get(i + 1)
+				.is_some_and(|states| states.contains(&st))
+		{
+			first_deviation = Some(i);
+		}
+	}
+
+	let goal_robot = Some(st.robots[goal.0]).filter(|&p| p != REMOVED);
+	let remaining = goal_robot
+		.and_then(|_| solve_from(bo, st, goal, optimal_length + moves.len()).map(|l| l.len()));
+	AnswerEvaluation {
+		optimal_length,
+		moves: moves.len(),
+		solved: goal_robot == Some(goal.1),
+		goal_robot,
+		remaining,
+		first_deviation,
+	}
+}
+
+/**
+ * The states on some optimal solution, by the number of moves from the start.
+ * A state is on one iff it is first reached by that many moves and the rest can be done in the remaining moves.
+ */
+fn optimal_states(bo: &Board, goal: (usize, Pos), optimal_length: usize) -> Vec<HashSet<State>> {
+	let layers = layered_bfs(bo, optimal_length);
+	let mut res = vec![HashSet::new(); optimal_length + 1];
+	res[optimal_length] = layers[optimal_length]
+		.keys()
+		.filter(|st| st.robots[goal.0] == goal.1)
+		.cloned()
+		.collect();
+	for i in (0..optimal_length).rev() {
+		let next = &res[i + 1];
+		let states = layers[i]
+			.keys()
+			.filter(|st| {
+				st.enumerate_states(bo)
+					.iter()
+					.any(|(ts, _)| next.contains(ts))
+			})
+			.cloned()
+			.collect();
+		res[i] = states;
+	}
+	res
+}
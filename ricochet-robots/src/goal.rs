@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{bfs, Board, Move, Pos, Prev, State, REMOVED, ROBOTS_COUNT};
+
+/// How `select_goal` picks the goal among the robot/cell pairs reached last.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GoalStrategy {
+	/// The pair `bfs` reaches last, which depends on the order the states are expanded.
+	Last,
+	/// Any pair first reached by the most moves, chosen uniformly.
+	Uniform,
+	/// Like `Uniform`, but only cells with a wall (or the border) on a vertical and a horizontal side,
+	/// like the targets of the physical game. Falls back to `Uniform` if no such cell is reached.
+	Corner,
+	/// Like `Uniform`, but only the pairs of this robot, which stays the goal robot when colours are shuffled.
+	Robot(usize),
+}
+
+impl FromStr for GoalStrategy {
+	type Err = String;
+
+	/// Parses `last`, `uniform`, `corner` or `robot:<colour index>`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut it = s.splitn(2, ':');
+		let name = it.next().unwrap_or("");
+		let arg = it.next();
+		match (name, arg) {
+			("last", None) => Ok(GoalStrategy::Last),
+			("uniform", None) => Ok(GoalStrategy::Uniform),
+			("corner", None) => Ok(GoalStrategy::Corner),
+			("robot", Some(arg)) => match arg.parse() {
+				Ok(c) if c < ROBOTS_COUNT => Ok(GoalStrategy::Robot(c)),
+				_ => Err(format!("invalid robot {:?}", arg)),
+			},
+			_ => Err(format!("unknown goal strategy {:?}", s)),
+		}
+	}
+}
+
+/// Writes the strategy as `from_str` reads it.
+impl fmt::Display for GoalStrategy {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match *self {
+			GoalStrategy::Last => write!(f, "last"),
+			GoalStrategy::Uniform => write!(f, "uniform"),
+			GoalStrategy::Corner => write!(f, "corner"),
+			GoalStrategy::Robot(c) => write!(f, "robot:{}", c),
+		}
+	}
+}
+
+impl GoalStrategy {
+	fn allows(&self, bo: &Board, c: usize, p: Pos) -> bool {
+		match *self {
+			GoalStrategy::Last | GoalStrategy::Uniform => true,
+			GoalStrategy::Corner => is_corner(bo, p),
+			GoalStrategy::Robot(robot) => c == robot,
+		}
+	}
+
+	/**
+	 * Swaps two colours after `randomize_colours` so that a `Robot` goal keeps its colour.
+	 * `goalcolour` and `log` are the shuffled ones, and the fixed ones are returned.
+	 */
+	pub(crate) fn keep_colour(
+		&self,
+		bo: &mut Board,
+		goalcolour: usize,
+		log: Vec<Move>,
+	) -> (usize, Vec<Move>) {
+		let robot = match *self {
+			GoalStrategy::Robot(robot) if robot != goalcolour => robot,
+			_ => return (goalcolour, log),
+		};
+		bo.robots.swap(robot, goalcolour);
+		let swap = |c| {
+			if c == robot {
+				goalcolour
+			} else if c == goalcolour {
+				robot
+			} else {
+				c
+			}
+		};
+		let log = log
+			.into_iter()
+			.map(|m| Move {
+				c: swap(m.c),
+				d: m.d,
+			})
+			.collect();
+		(robot, log)
+	}
+}
+
+/// Whether a robot on the cell is stopped both vertically and horizontally.
+pub(crate) fn is_corner(bo: &Board, p: Pos) -> bool {
+	let walldist = &bo.walldist[p.y as usize][p.x as usize];
+	(walldist[0] == 0 || walldist[2] == 0) && (walldist[1] == 0 || walldist[3] == 0)
+}
+
+/**
+ * Same as `bfs`, but picks the goal by `strategy` among the pairs allowed by it
+ * which are first reached by the most moves, up to `target` moves.
+ * Unlike `bfs`, the whole last layer is looked at, and the moves are returned in the reverse order as well.
+ * The strategy which picked the goal comes first, as `Corner` falls back to `Uniform`.
+ */
+pub fn select_goal<R: rand::Rng>(
+	target: u8,
+	bo: &Board,
+	strategy: GoalStrategy,
+	rng: &mut R,
+) -> (GoalStrategy, (usize, Pos), Vec<Move>) {
+	if strategy == GoalStrategy::Last {
+		let (goal, log) = bfs(target, bo);
+		return (strategy, goal, log);
+	}
+
+	let init = State::init_state(bo);
+	let mut prev: HashMap<State, Prev> = HashMap::new();
+	prev.insert(init.clone(), Prev::dummy());
+	let mut found = vec![vec![[false; ROBOTS_COUNT]; bo.w]; bo.h];
+	let mut found_count = 0;
+	// Once every allowed pair is found, the deeper layers can't have candidates.
+	let mut allowed_num = 0;
+	for y in 0..bo.h {
+		for x in 0..bo.w {
+			let p = Pos {
+				y: y as i8,
+				x: x as i8,
+			};
+			if !bo.is_blocked(y, x) && !bo.is_hole(p) {
+				allowed_num += (0..ROBOTS_COUNT)
+					.filter(|&c| strategy.allows(bo, c, p))
+					.count();
+			}
+		}
+	}
+
+	// The allowed pairs of the deepest layer reaching any, with a state where the pair is.
+	let mut candidates: Vec<((usize, Pos), State)> = vec![];
+	let mut layer = vec![init.clone()];
+	for depth in 0..=target {
+		let mut reached = vec![];
+		for st in &layer {
+			for (i, &p) in st.robots.iter().enumerate() {
+				if p != REMOVED && !found[p.y as usize][p.x as usize][i] {
+					found[p.y as usize][p.x as usize][i] = true;
+					if strategy.allows(bo, i, p) {
+						found_count += 1;
+						reached.push(((i, p), st.clone()));
+					}
+				}
+			}
+		}
+		if !reached.is_empty() {
+			candidates = reached;
+		}
+		if depth == target || found_count >= allowed_num {
+			break;
+		}
+
+		let mut next = vec![];
+		for st in &layer {
+			for (ts, m) in st.enumerate_states(bo) {
+				prev.entry(ts.clone()).or_insert_with(|| {
+					next.push(ts);
+					Prev::serialize(&m, &st.robots[m.c])
+				});
+			}
+		}
+		if next.is_empty() {
+			break;
+		}
+		layer = next;
+	}
+
+	if candidates.is_empty() {
+		return select_goal(target, bo, GoalStrategy::Uniform, rng);
+	}
+	let (goal, mut s) = candidates.swap_remove(rng.gen_range(0, candidates.len()));
+
+	// path reconstruction
+	let mut l = vec![];
+	while s != init {
+		let (m, p) = prev[&s].deserialize();
+		l.push(m);
+		s.robots[m.c] = p;
+	}
+
+	(strategy, goal, l)
+}
@@ -0,0 +1,1232 @@
+use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+extern crate xorshift;
+use xorshift::{Rng, Xorshift128};
+pub type BoardRng = Xorshift128;
+
+extern crate rand;
+use rand::prelude::SliceRandom;
+
+extern crate serde_derive;
+use serde_derive::{Deserialize, Serialize};
+
+use std::cmp;
+
+pub mod analysis;
+pub mod bidirectional;
+pub mod cost;
+pub mod design;
+pub mod difficulty;
+pub mod evaluate;
+pub mod goal;
+pub mod notation;
+pub mod policy;
+pub mod pool;
+pub mod preset;
+pub mod shape;
+#[cfg(test)]
+mod tests;
+use difficulty::{DifficultyReport, DifficultyWeights};
+use goal::{select_goal, GoalStrategy};
+use policy::WallPolicy;
+use shape::Shape;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Pos {
+	pub y: i8,
+	pub x: i8,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WallPos {
+	pub y: i8,
+	pub x: i8,
+	pub d: i8,
+}
+
+pub const ROBOTS_COUNT: usize = 4;
+
+#[derive(Debug, Clone)]
+pub struct Board {
+	w: usize,
+	h: usize,
+	walls: Vec<WallPos>,
+	walldist: Vec<Vec<[usize; 4]>>,
+	robots: [Pos; ROBOTS_COUNT],
+	blocked: Vec<Pos>,
+	holes: Vec<Pos>,
+	torus: bool,
+	teleporters: Vec<[Pos; 2]>,
+	sticky: Vec<Pos>,
+	ice: Vec<Pos>,
+}
+
+/// Options of `Board::with_options` other than the size and the number of walls.
+#[derive(Debug, Clone, Copy)]
+pub struct BoardOptions {
+	pub policy: WallPolicy,
+	pub shape: Shape,
+	/// The number of cells which remove a robot stopping on them.
+	pub hole_num: usize,
+	/// Leaving an edge re-enters from the opposite edge (ループロボット).
+	pub torus: bool,
+	/// The number of pairs of teleporter pads.
+	pub teleporter_num: usize,
+	/// The number of cells which stop a robot sliding onto them.
+	pub sticky_num: usize,
+	/// The number of ice cells. They don't change the moves and are only drawn differently.
+	pub ice_num: usize,
+	/// `generate_problem` retries until the answer has at least this many moves.
+	pub min_length: usize,
+	/// `generate_problem` retries until every optimal solution moves at least this many robots.
+	pub min_robots: usize,
+	/// `generate_problem` retries until every optimal solution has the goal robot stop against another robot.
+	pub require_blocker: bool,
+	/// How `generate_problem` picks the goal.
+	pub goal: GoalStrategy,
+}
+
+impl Default for BoardOptions {
+	fn default() -> Self {
+		BoardOptions {
+			policy: WallPolicy::RandomPairs,
+			shape: Shape::Rectangle,
+			hole_num: 0,
+			torus: false,
+			teleporter_num: 0,
+			sticky_num: 0,
+			ice_num: 0,
+			min_length: 0,
+			min_robots: 0,
+			require_blocker: false,
+			goal: GoalStrategy::Last,
+		}
+	}
+}
+
+impl BoardOptions {
+	/// Checks that a board of the size can be generated with these options.
+	pub fn check(&self, board_h: usize, board_w: usize, wall_num: usize) -> Result<(), String> {
+		// `Prev` packs the coordinates into 6 bits, and 63 is taken by removed robots.
+		if board_h == 0 || board_w == 0 || board_h >= 63 || board_w >= 63 {
+			return Err(format!(
+				"the board must be between 1x1 and 62x62, got {}x{}",
+				board_h, board_w
+			));
+		}
+		let inner_edges = (board_h - 1) * board_w + board_h * (board_w - 1);
+		if wall_num > inner_edges {
+			return Err(format!(
+				"{} walls don't fit on a {}x{} board with {} inner edges",
+				wall_num, board_h, board_w, inner_edges
+			));
+		}
+		// The robots are put on cells without holes, teleporters and terrain until all of them fit.
+		let open = board_h * board_w - self.shape.blocked_cells(board_h, board_w).len();
+		let needed =
+			ROBOTS_COUNT + self.hole_num + 2 * self.teleporter_num + self.sticky_num + self.ice_num;
+		if open < needed {
+			return Err(format!(
+				"the board has {} open cells, but the robots, holes, teleporters and terrain need {}",
+				open, needed
+			));
+		}
+		if self.min_robots > ROBOTS_COUNT {
+			return Err(format!(
+				"there are only {} robots to move, got {}",
+				ROBOTS_COUNT, self.min_robots
+			));
+		}
+		Ok(())
+	}
+
+	/**
+	 * Whether the puzzle is one `generate_problem` keeps: the answer is long enough,
+	 * the teleporters are needed if there are any, and the optimal solutions involve the robots as required.
+	 */
+	pub fn accepts(&self, bo: &Board, goal: (usize, Pos), length: usize) -> bool {
+		if length < self.min_length {
+			return false;
+		}
+		if self.teleporter_num > 0 && !teleporters_matter(bo, goal, length) {
+			return false;
+		}
+		if self.min_robots > 1 || self.require_blocker {
+			let report = DifficultyReport::new(bo, goal, length, &DifficultyWeights::default());
+			if report.robots_moved < self.min_robots
+				|| (self.require_blocker && report.blocker_stops == 0)
+			{
+				return false;
+			}
+		}
+		true
+	}
+}
+
+/// The position of a robot which fell into a hole. It never blocks other robots.
+const REMOVED: Pos = Pos { y: 63, x: 63 };
+
+/// The value of `walldist` on a torus board when the row or column has no wall in that direction.
+const NO_WALL: usize = 127;
+
+const DIRECTIONS: [Pos; 4] = [
+	Pos { y: 1, x: 0 },
+	Pos { y: 0, x: 1 },
+	Pos { y: -1, x: 0 },
+	Pos { y: 0, x: -1 },
+];
+
+impl Board {
+	fn good_board(&mut self) -> bool {
+		let mut gone = vec![vec![false; self.w]; self.h];
+
+		fn dfs(gone: &mut Vec<Vec<bool>>, self_: &Board, y: usize, x: usize) -> usize {
+			//println!("{} {}",y,x);
+			if gone[y][x] {
+				return 0;
+			}
+			gone[y][x] = true;
+			let mut res = 1;
+			for (i, dir) in DIRECTIONS.iter().enumerate() {
+				if self_.walldist[y][x][i] == 0 {
+					continue;
+				}
+				// Only torus boards have open edges on the border.
+				let ty = (y as i8 + dir.y).rem_euclid(self_.h as i8) as usize;
+				let tx = (x as i8 + dir.x).rem_euclid(self_.w as i8) as usize;
+				res += dfs(gone, self_, ty, tx);
+			}
+			res
+		}
+
+		let start = (0..self.h * self.w)
+			.map(|i| (i / self.w, i % self.w))
+			.find(|&(y, x)| !self.is_blocked(y, x));
+		let cn = match start {
+			Some((y, x)) => dfs(&mut gone, self, y, x),
+			None => 0,
+		};
+		//println!("{}",cn);
+		if cn != self.h * self.w - self.blocked.len() {
+			// all cells aren't connected
+			return false;
+		}
+
+		//println!("{:?}",self.board);
+
+		for y in 0..self.h {
+			for x in 0..self.w {
+				if self.is_blocked(y, x) {
+					continue;
+				}
+				let mut d = 0;
+				for i in 0..4 {
+					if self.walldist[y][x][i] > 0 {
+						d += 1;
+					}
+				}
+				//println!("{}",d);
+				if d < 2 {
+					// This cell is not interesting.
+					return false;
+				}
+			}
+		}
+
+		true
+	}
+
+	fn is_blocked(&self, y: usize, x: usize) -> bool {
+		self.blocked
+			.iter()
+			.any(|p| p.y as usize == y && p.x as usize == x)
+	}
+
+	fn is_hole(&self, p: Pos) -> bool {
+		!self.holes.is_empty() && self.holes.contains(&p)
+	}
+
+	/// The other pad of the pair, if `p` is a teleporter pad.
+	fn teleporter_partner(&self, p: Pos) -> Option<Pos> {
+		self.teleporters.iter().find_map(|pads| {
+			if pads[0] == p {
+				Some(pads[1])
+			} else if pads[1] == p {
+				Some(pads[0])
+			} else {
+				None
+			}
+		})
+	}
+
+	fn is_sticky(&self, p: Pos) -> bool {
+		!self.sticky.is_empty() && self.sticky.contains(&p)
+	}
+
+	/// The number of cells a robot can stay on.
+	fn open_cells(&self) -> usize {
+		self.h * self.w - self.blocked.len() - self.holes.len()
+	}
+
+	/// Surrounds the blocked cell with walls, so that no robot enters it.
+	fn put_block(&mut self, p: Pos) {
+		let (y, x) = (p.y, p.x);
+		for wall in &[
+			WallPos { y, x, d: 0 },
+			WallPos { y: y + 1, x, d: 0 },
+			WallPos { y, x, d: 1 },
+			WallPos { y, x: x + 1, d: 1 },
+		] {
+			self.put_wall(wall);
+		}
+	}
+
+	/**
+	 * Makes robots sliding onto the sticky cell stop there, like a wall right behind it.
+	 * A robot standing on the cell leaves it as usual.
+	 */
+	fn put_sticky(&mut self, p: Pos) {
+		let (y, x) = (p.y as usize, p.x as usize);
+		let (h, w) = (self.h, self.w);
+		for ty in 0..h {
+			if ty < y || (self.torus && ty != y) {
+				self.walldist[ty][x][0] = cmp::min((y + h - ty) % h, self.walldist[ty][x][0]);
+			}
+			if ty > y || (self.torus && ty != y) {
+				self.walldist[ty][x][2] = cmp::min((ty + h - y) % h, self.walldist[ty][x][2]);
+			}
+		}
+		for tx in 0..w {
+			if tx < x || (self.torus && tx != x) {
+				self.walldist[y][tx][1] = cmp::min((x + w - tx) % w, self.walldist[y][tx][1]);
+			}
+			if tx > x || (self.torus && tx != x) {
+				self.walldist[y][tx][3] = cmp::min((tx + w - x) % w, self.walldist[y][tx][3]);
+			}
+		}
+	}
+
+	/**
+	 * On a torus board, a robot in a row or a column without walls would slide forever.
+	 * Puts a wall in each of them where possible.
+	 */
+	fn close_loops(&mut self, rng: &mut BoardRng) {
+		for y in 0..self.h {
+			for _ in 0..self.w {
+				if self.walldist[y][0][1] != NO_WALL {
+					break;
+				}
+				let x = rng.gen_range(0, self.w);
+				self.try_add_walls(vec![WallPos {
+					y: y as i8,
+					x: x as i8,
+					d: 1,
+				}]);
+			}
+		}
+		for x in 0..self.w {
+			for _ in 0..self.h {
+				if self.walldist[0][x][0] != NO_WALL {
+					break;
+				}
+				let y = rng.gen_range(0, self.h);
+				self.try_add_walls(vec![WallPos {
+					y: y as i8,
+					x: x as i8,
+					d: 0,
+				}]);
+			}
+		}
+	}
+
+	/**
+	 * Puts a wall and shortens the distances to the walls around it.
+	 * Walls on the border of the board are ignored, except on torus boards
+	 * where they separate the first and the last row (or column).
+	 */
+	fn put_wall(&mut self, wall: &WallPos) {
+		let (y, x) = (wall.y as usize, wall.x as usize);
+		if self.torus {
+			let (h, w) = (self.h, self.w);
+			if wall.d == 0 {
+				let y = y % h;
+				for ty in 0..h {
+					self.walldist[ty][x][0] =
+						cmp::min((y + h - 1 - ty) % h, self.walldist[ty][x][0]);
+					self.walldist[ty][x][2] = cmp::min((ty + h - y) % h, self.walldist[ty][x][2]);
+				}
+			} else {
+				let x = x % w;
+				for tx in 0..w {
+					self.walldist[y][tx][1] =
+						cmp::min((x + w - 1 - tx) % w, self.walldist[y][tx][1]);
+					self.walldist[y][tx][3] = cmp::min((tx + w - x) % w, self.walldist[y][tx][3]);
+				}
+			}
+		} else if wall.d == 0 {
+			if 0 < y && y < self.h {
+				for ty in 0..y {
+					self.walldist[ty][x][0] = cmp::min(y - 1 - ty, self.walldist[ty][x][0]);
+				}
+				for ty in y..self.h {
+					self.walldist[ty][x][2] = cmp::min(ty - y, self.walldist[ty][x][2]);
+				}
+			}
+		} else if 0 < x && x < self.w {
+			for tx in 0..x {
+				self.walldist[y][tx][1] = cmp::min(x - 1 - tx, self.walldist[y][tx][1]);
+			}
+			for tx in x..self.w {
+				self.walldist[y][tx][3] = cmp::min(tx - x, self.walldist[y][tx][3]);
+			}
+		}
+	}
+
+	/**
+	 * Adds the walls only if the board stays good afterwards.
+	 * Returns whether the walls were added.
+	 */
+	fn try_add_walls(&mut self, mut add_walls: Vec<WallPos>) -> bool {
+		let mem_walldist = self.walldist.clone();
+		for wall in &add_walls {
+			self.put_wall(wall);
+		}
+
+		if self.good_board() {
+			self.walls.append(&mut add_walls);
+			true
+		} else {
+			self.walldist = mem_walldist;
+			false
+		}
+	}
+
+	/// Sets `walldist` to the distances on the board without any wall.
+	fn clear_walldist(&mut self) {
+		self.walldist = vec![vec![[0; 4]; self.w]; self.h];
+		for y in 0..self.h {
+			for x in 0..self.w {
+				self.walldist[y][x] = if self.torus {
+					[NO_WALL; 4]
+				} else {
+					[self.h - 1 - y, self.w - 1 - x, y, x]
+				};
+			}
+		}
+	}
+
+	/// Recomputes `walldist` from the blocked cells, the walls and the sticky cells.
+	fn rebuild_walldist(&mut self) {
+		self.clear_walldist();
+		for p in self.blocked.clone() {
+			self.put_block(p);
+		}
+		for wall in self.walls.clone() {
+			self.put_wall(&wall);
+		}
+		for p in self.sticky.clone() {
+			self.put_sticky(p);
+		}
+	}
+
+	fn init(
+		&mut self,
+		mut rng: BoardRng,
+		wall_num: usize,
+		options: &BoardOptions,
+	) -> Result<(), String> {
+		//println!("{} {} {} {}",self.board.len(), self.h, self.board[0].len(), self.w);
+		// The first number used to be printed. It is still drawn so that a seed gives the same board.
+		rng.gen_range(0, 1000);
+		self.torus = options.torus;
+		self.clear_walldist();
+
+		self.blocked = options.shape.blocked_cells(self.h, self.w);
+		for p in self.blocked.clone() {
+			self.put_block(p);
+		}
+
+		options.policy.place_walls(self, &mut rng, wall_num);
+		if self.torus {
+			self.close_loops(&mut rng);
+		}
+
+		// Holes are placed after the walls, as they don't affect the connectivity.
+		let mut tries = 0;
+		while self.holes.len() < options.hole_num && tries < 100 * options.hole_num {
+			tries += 1;
+			let tp = Pos {
+				y: rng.gen_range(0, self.h) as i8,
+				x: rng.gen_range(0, self.w) as i8,
+			};
+			if !self.is_blocked(tp.y as usize, tp.x as usize) && !self.is_hole(tp) {
+				self.holes.push(tp);
+			}
+		}
+
+		let free = |bo: &Board, p: Pos| {
+			!bo.is_blocked(p.y as usize, p.x as usize)
+				&& !bo.is_hole(p)
+				&& bo.teleporter_partner(p).is_none()
+		};
+		let mut tries = 0;
+		while self.teleporters.len() < options.teleporter_num
+			&& tries < 100 * options.teleporter_num
+		{
+			tries += 1;
+			let mut pads = [Pos { y: 0, x: 0 }; 2];
+			for pad in pads.iter_mut() {
+				*pad = Pos {
+					y: rng.gen_range(0, self.h) as i8,
+					x: rng.gen_range(0, self.w) as i8,
+				};
+			}
+			if pads[0] != pads[1] && free(self, pads[0]) && free(self, pads[1]) {
+				self.teleporters.push(pads);
+			}
+		}
+
+		// Terrain goes after the walls, so that `walldist` of the walls is already in place.
+		let free = |bo: &Board, p: Pos| free(bo, p) && !bo.is_sticky(p) && !bo.ice.contains(&p);
+		for &(num, sticky) in &[(options.sticky_num, true), (options.ice_num, false)] {
+			let mut placed = 0;
+			let mut tries = 0;
+			while placed < num && tries < 100 * num {
+				tries += 1;
+				let tp = Pos {
+					y: rng.gen_range(0, self.h) as i8,
+					x: rng.gen_range(0, self.w) as i8,
+				};
+				if !free(self, tp) {
+					continue;
+				}
+				if sticky {
+					self.put_sticky(tp);
+					self.sticky.push(tp);
+				} else {
+					self.ice.push(tp);
+				}
+				placed += 1;
+			}
+		}
+
+		let mut i = 0;
+		let mut tries = 0;
+		while i < 4 {
+			// Every free cell is picked by then unless there are too few of them.
+			if tries >= 100 * self.h * self.w {
+				return Err(format!("only {} of the robots fit on the board", i));
+			}
+			tries += 1;
+			let tp = Pos {
+				y: rng.gen_range(0, self.h) as i8,
+				x: rng.gen_range(0, self.w) as i8,
+			};
+			let mut ok = free(self, tp);
+			for j in 0..i {
+				ok &= tp != self.robots[j];
+			}
+			if ok {
+				self.robots[i] = tp;
+				i += 1;
+			}
+		}
+		Ok(())
+	}
+	/// Panics if the robots don't fit on the board. See `Board::with_options`.
+	pub fn new(board_h: usize, board_w: usize, rng: BoardRng, wall_num: usize) -> Board {
+		Board::with_policy(board_h, board_w, rng, wall_num, &WallPolicy::RandomPairs)
+	}
+
+	pub fn with_policy(
+		board_h: usize,
+		board_w: usize,
+		rng: BoardRng,
+		wall_num: usize,
+		policy: &WallPolicy,
+	) -> Board {
+		let options = BoardOptions {
+			policy: *policy,
+			..BoardOptions::default()
+		};
+		Board::with_options(board_h, board_w, rng, wall_num, &options)
+			.expect("the robots don't fit on the board")
+	}
+
+	/// Fails if the robots don't fit on the cells left free, which `BoardOptions::check` rules out.
+	pub fn with_options(
+		board_h: usize,
+		board_w: usize,
+		rng: BoardRng,
+		wall_num: usize,
+		options: &BoardOptions,
+	) -> Result<Board, String> {
+		let mut res = Board {
+			w: board_w,
+			h: board_h,
+			walls: vec![],
+			walldist: vec![],
+			robots: [Pos { y: 0, x: 0 }; ROBOTS_COUNT],
+			blocked: vec![],
+			holes: vec![],
+			torus: false,
+			teleporters: vec![],
+			sticky: vec![],
+			ice: vec![],
+		};
+		res.init(rng, wall_num, options)?;
+		Ok(res)
+	}
+
+	/// The position of the robot, or `None` if it fell into a hole.
+	pub fn robot(&self, i: usize) -> Option<Pos> {
+		Some(self.robots[i]).filter(|&p| p != REMOVED)
+	}
+
+	/// The moves which move some robot.
+	pub fn legal_moves(&self) -> Vec<Move> {
+		State::init_state(self)
+			.enumerate_states(self)
+			.into_iter()
+			.map(|(_, m)| m)
+			.collect()
+	}
+
+	/// Moves a robot on the board. Returns `false` and leaves the board as it is if the robot can't go that way.
+	pub fn apply_move(&mut self, m: &Move) -> bool {
+		match State::init_state(self).move_to(self, m.c, m.d) {
+			Some(st) => {
+				self.robots = st.robots;
+				true
+			}
+			None => false,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Move {
+	pub c: usize,
+	pub d: usize,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+struct State {
+	robots: [Pos; ROBOTS_COUNT],
+}
+
+impl State {
+	pub fn init_state(bo: &Board) -> State {
+		State { robots: bo.robots }
+	}
+
+	fn move_to(&self, board: &Board, robot_index: usize, robot_dir: usize) -> Option<State> {
+		let dir = &DIRECTIONS[robot_dir];
+		let mut p = self.robots[robot_index];
+		if p == REMOVED {
+			return None;
+		}
+		if !board.teleporters.is_empty() {
+			return self.move_to_stepwise(board, robot_index, robot_dir);
+		}
+		if board.torus {
+			return self.move_to_torus(board, robot_index, robot_dir);
+		}
+		let mut mind = board.walldist[p.y as usize][p.x as usize][robot_dir] as i8;
+		//removing "as i8" by changing type of walldist doesn't make well difference.
+
+		// if mind == 0 { return None } //pruning with little (0.2~3sec) speedup.
+		/*
+		if robot_dir == 2 {
+			for j in 0..4 {
+				if j != robot_index {
+					if self.robots[j].x == p.x && self.robots[j].y < p.y {
+						mind = cmp::min(mind,p.y - self.robots[j].y - 1);
+					}
+				}
+			}
+		} else if robot_dir == 0 {
+			for j in 0..4 {
+				if j != robot_index {
+					if self.robots[j].x == p.x && self.robots[j].y > p.y {
+						mind = cmp::min(mind,self.robots[j].y - p.y - 1);
+					}
+				}
+			}
+		} else {
+			for j in 0..4 {
+				if j != robot_index {
+					let dx = self.robots[j].x - p.x;
+					if dx.signum() == dir.x.signum() && self.robots[j].y == p.y {
+						mind = cmp::min(mind,dx.abs()-1);
+					}
+				}
+			}
+		}
+		//unloling also has little speedup (0.2~0.3 sec)
+		*/
+		for j in 0..4 {
+			if j != robot_index {
+				let dx = self.robots[j].x - p.x;
+				let dy = self.robots[j].y - p.y;
+				if dx.signum() == dir.x.signum() && dy.signum() == dir.y.signum() {
+					if dx.signum() == 0 {
+						mind = cmp::min(mind, dy.abs() - 1);
+					} else {
+						mind = cmp::min(mind, dx.abs() - 1);
+					}
+				}
+			}
+		}
+
+		if mind == 0 {
+			return None;
+		}
+
+		p = Pos {
+			y: p.y + dir.y * mind,
+			x: p.x + dir.x * mind,
+		};
+		if board.is_hole(p) {
+			p = REMOVED;
+		}
+
+		let mut res = State {
+			robots: self.robots,
+		};
+		res.robots[robot_index] = p;
+		Some(res)
+	}
+
+	/// Same as `move_to`, but the robot slides over the edges.
+	fn move_to_torus(&self, board: &Board, robot_index: usize, robot_dir: usize) -> Option<State> {
+		let dir = &DIRECTIONS[robot_dir];
+		let p = self.robots[robot_index];
+		let (h, w) = (board.h as i8, board.w as i8);
+		let mut mind = board.walldist[p.y as usize][p.x as usize][robot_dir] as i8;
+		for (j, q) in self.robots.iter().enumerate() {
+			if j == robot_index {
+				continue;
+			}
+			let dist = if dir.x == 0 && q.x == p.x {
+				((q.y - p.y) * dir.y).rem_euclid(h)
+			} else if dir.y == 0 && q.y == p.y {
+				((q.x - p.x) * dir.x).rem_euclid(w)
+			} else {
+				continue;
+			};
+			mind = cmp::min(mind, dist - 1);
+		}
+
+		if mind == 0 || mind == NO_WALL as i8 {
+			return None;
+		}
+
+		let mut p = Pos {
+			y: (p.y + dir.y * mind).rem_euclid(h),
+			x: (p.x + dir.x * mind).rem_euclid(w),
+		};
+		if board.is_hole(p) {
+			p = REMOVED;
+		}
+
+		let mut res = State {
+			robots: self.robots,
+		};
+		res.robots[robot_index] = p;
+		Some(res)
+	}
+
+	/**
+	 * Same as `move_to`, but slides the robot cell by cell for boards with teleporters.
+	 * A robot entering a pad comes out of the partner pad and keeps its direction,
+	 * unless another robot stands on the partner pad.
+	 * Returns `None` if the robot would slide forever.
+	 */
+	fn move_to_stepwise(
+		&self,
+		board: &Board,
+		robot_index: usize,
+		robot_dir: usize,
+	) -> Option<State> {
+		let dir = &DIRECTIONS[robot_dir];
+		let (h, w) = (board.h as i8, board.w as i8);
+		let start = self.robots[robot_index];
+		let occupied = |p: Pos| {
+			self.robots
+				.iter()
+				.enumerate()
+				.any(|(j, &q)| j != robot_index && q == p)
+		};
+
+		let mut visited = vec![vec![false; board.w]; board.h];
+		visited[start.y as usize][start.x as usize] = true;
+		let mut p = start;
+		while board.walldist[p.y as usize][p.x as usize][robot_dir] > 0 {
+			let q = Pos {
+				y: (p.y + dir.y).rem_euclid(h),
+				x: (p.x + dir.x).rem_euclid(w),
+			};
+			if occupied(q) {
+				break;
+			}
+			p = q;
+			if board.is_sticky(p) {
+				break;
+			}
+			if let Some(partner) = board.teleporter_partner(p) {
+				if !occupied(partner) {
+					p = partner;
+				}
+			}
+			// The robot moves deterministically, so coming back to a cell means a loop.
+			if visited[p.y as usize][p.x as usize] {
+				return None;
+			}
+			visited[p.y as usize][p.x as usize] = true;
+		}
+
+		if p == start {
+			return None;
+		}
+		if board.is_hole(p) {
+			p = REMOVED;
+		}
+
+		let mut res = State {
+			robots: self.robots,
+		};
+		res.robots[robot_index] = p;
+		Some(res)
+	}
+
+	fn enumerate_states(&self, board: &Board) -> Vec<(State, Move)> {
+		let mut res = Vec::with_capacity(16);
+		for i in 0..self.robots.len() {
+			for j in 0..4 {
+				if let Some(ts) = self.move_to(board, i, j) {
+					res.push((ts, Move { c: i, d: j }));
+				}
+			}
+		}
+		res
+	}
+}
+
+impl Hash for State {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		//Surprisingly, this makes program very fast!
+		//:waiwai:
+		let mut bits: u64 = 0;
+		for i in 0..ROBOTS_COUNT {
+			let p = self.robots[i];
+			bits |= (((p.y as u64) << 8) | (p.x as u64)) << (i * 16);
+		}
+		bits.hash(state);
+	}
+}
+
+/**
+ * Its internal representation is like below:
+ *
+ * ```text
+ *                  0b_00000000_00000000
+ *     robot_index     ^^
+ *       robot_dir       ^^
+ *          prev_y         ^^^^ ^^
+ *          prev_x                ^^^^^^
+ * ```
+ *
+ * Assume that
+ *   - the number of robots < 4 and
+ *   - the width and height of the board < 64.
+ *
+ * Making the data compact increases speed a little. (ura)
+ */
+struct Prev(u16);
+
+impl Prev {
+	/**
+	 * The initial state has no previous state, so use this dummy value.
+	 * However, there is no mechanism to check if it is a dummy or not,
+	 * so please check if the state is the initial state or not.
+	 */
+	fn dummy() -> Self {
+		Prev(!0)
+	}
+
+	fn serialize(m: &Move, p: &Pos) -> Self {
+		let prev = ((m.c as u16) << 14) | ((m.d as u16) << 12) | ((p.y as u16) << 6) | (p.x as u16);
+		Prev(prev)
+	}
+
+	fn deserialize(&self) -> (Move, Pos) {
+		let robot_index = (self.0 >> 14) as usize;
+		let robot_dir = ((self.0 >> 12) & 0b11) as usize;
+		let prev_y = ((self.0 >> 6) & 0b111111) as i8;
+		let prev_x = (self.0 & 0b111111) as i8;
+		(
+			Move {
+				c: robot_index,
+				d: robot_dir,
+			},
+			Pos {
+				y: prev_y,
+				x: prev_x,
+			},
+		)
+	}
+}
+
+pub fn bfs(target: u8, bo: &Board) -> ((usize, Pos), Vec<Move>) {
+	let init = State::init_state(bo);
+	let mut last_state = init.clone();
+	let mut goal = (0, init.robots[0]);
+
+	let mut prev: HashMap<State, Prev> = HashMap::new();
+	prev.insert(init.clone(), Prev::dummy());
+
+	let mut que = VecDeque::new();
+	let mut depth: usize = 0;
+	que.push_back(Some(init.clone()));
+	que.push_back(None);
+
+	let mut found = vec![vec![[false; ROBOTS_COUNT]; bo.w]; bo.h];
+	let mut found_count = 0;
+	// Teleporter pads are counted, as a robot stops on one when the partner pad is taken.
+	// Pairs which can't be reached at all end the search by running out of states instead.
+	let max_pattern_num = bo.open_cells() * bo.robots.len();
+
+	while let Some(st) = que.pop_front() {
+		match st {
+			Some(st) => {
+				//println!("{:?}",st.robots);
+				let mut ok = false;
+				for (i, &p) in st.robots.iter().enumerate() {
+					if p != REMOVED && !found[p.y as usize][p.x as usize][i] {
+						//println!("{} {} {} : {} ",p.y,p.x,i,depth);
+						found[p.y as usize][p.x as usize][i] = true;
+						found_count += 1;
+						goal = (i, p);
+						// The search may run out of states before `target`, so remember where the goal is.
+						last_state = st.clone();
+						if depth >= target as usize || found_count >= max_pattern_num {
+							ok = true;
+							break;
+						}
+					}
+				}
+				if ok {
+					break;
+				}
+				for (ts, m) in st.enumerate_states(bo) {
+					// kcz-san and satos-san say that performing `push_back` here
+					// decreases speed, but this is necessary for path reconstruction.
+					// However, using `entry` instead of `contains_key` and `insert`
+					// increases speed a bit. (ura)
+					prev.entry(ts.clone()).or_insert_with(|| {
+						que.push_back(Some(ts));
+						let p = st.robots[m.c];
+						Prev::serialize(&m, &p)
+					});
+				}
+			}
+			None => {
+				depth += 1;
+				// Only the marker was left, so every reachable state has been seen.
+				if depth > target as usize || que.is_empty() {
+					break;
+				}
+				que.push_back(None);
+			}
+		}
+	}
+
+	// path reconstruction
+	let mut l = vec![];
+	let mut s = last_state;
+	while s != init {
+		let (m, p) = prev[&s].deserialize();
+		l.push(m);
+		s.robots[m.c] = p;
+	}
+
+	(goal, l)
+}
+
+/// Entry points to the private parts of the engine for `benches/engine.rs`.
+#[doc(hidden)]
+pub mod bench_hooks {
+	use super::*;
+
+	/// Hashes the start state like the `HashMap`s of `bfs` do.
+	pub fn hash_start<H: Hasher + Default>(bo: &Board) -> u64 {
+		let mut hasher = H::default();
+		State::init_state(bo).hash(&mut hasher);
+		hasher.finish()
+	}
+
+	pub fn move_start(bo: &Board, robot_index: usize, robot_dir: usize) -> bool {
+		State::init_state(bo)
+			.move_to(bo, robot_index, robot_dir)
+			.is_some()
+	}
+
+	/// The number of states one move away from the start.
+	pub fn enumerate_start(bo: &Board) -> usize {
+		State::init_state(bo).enumerate_states(bo).len()
+	}
+
+	pub fn prev_round_trip(m: &Move, p: &Pos) -> (Move, Pos) {
+		Prev::serialize(m, p).deserialize()
+	}
+}
+
+/**
+ * Expands the states layer by layer up to `length` moves.
+ * The `i`-th layer maps each state first reached by `i` moves to the number of such move sequences.
+ */
+pub(crate) fn layered_bfs(bo: &Board, length: usize) -> Vec<HashMap<State, u64>> {
+	let init = State::init_state(bo);
+	let mut visited: HashSet<State> = HashSet::new();
+	visited.insert(init.clone());
+
+	let mut layer: HashMap<State, u64> = HashMap::new();
+	layer.insert(init, 1);
+	let mut layers = Vec::with_capacity(length + 1);
+	for _ in 0..length {
+		let mut next: HashMap<State, u64> = HashMap::new();
+		for (st, cnt) in &layer {
+			for (ts, _) in st.enumerate_states(bo) {
+				if visited.contains(&ts) {
+					continue;
+				}
+				let e = next.entry(ts).or_insert(0);
+				*e = e.saturating_add(*cnt);
+			}
+		}
+		visited.extend(next.keys().cloned());
+		layers.push(layer);
+		layer = next;
+	}
+	layers.push(layer);
+	layers
+}
+
+/**
+ * Counts the shortest move sequences that bring the robot `goal.0` onto `goal.1`,
+ * assuming that `length` is the optimal number of moves.
+ * Every state is counted once per layer, so this is as cheap as a plain BFS.
+ */
+pub fn count_optimal_solutions(bo: &Board, goal: (usize, Pos), length: usize) -> u64 {
+	layered_bfs(bo, length)[length]
+		.iter()
+		.filter(|(st, _)| st.robots[goal.0] == goal.1)
+		.fold(0, |acc, (_, cnt)| acc.saturating_add(*cnt))
+}
+
+/**
+ * Finds a shortest move sequence, in the playing order, that brings the robot `goal.0` onto `goal.1`.
+ * Returns `None` if it needs more than `max_depth` moves.
+ */
+pub fn solve(bo: &Board, goal: (usize, Pos), max_depth: usize) -> Option<Vec<Move>> {
+	solve_from(bo, State::init_state(bo), goal, max_depth)
+}
+
+/// Same as `solve`, but starts from `init` instead of the robots on the board.
+pub(crate) fn solve_from(
+	bo: &Board,
+	init: State,
+	goal: (usize, Pos),
+	max_depth: usize,
+) -> Option<Vec<Move>> {
+	let mut prev: HashMap<State, Prev> = HashMap::new();
+	prev.insert(init.clone(), Prev::dummy());
+
+	let mut layer = vec![init.clone()];
+	let mut depth = 0;
+	let mut last_state = None;
+	while !layer.is_empty() {
+		if let Some(st) = layer.iter().find(|st| st.robots[goal.0] == goal.1) {
+			last_state = Some(st.clone());
+			break;
+		}
+		if depth == max_depth {
+			break;
+		}
+		let mut next = vec![];
+		for st in &layer {
+			for (ts, m) in st.enumerate_states(bo) {
+				prev.entry(ts.clone()).or_insert_with(|| {
+					next.push(ts);
+					Prev::serialize(&m, &st.robots[m.c])
+				});
+			}
+		}
+		layer = next;
+		depth += 1;
+	}
+
+	let mut s = last_state?;
+	let mut l = vec![];
+	while s != init {
+		let (m, p) = prev[&s].deserialize();
+		l.push(m);
+		s.robots[m.c] = p;
+	}
+	l.reverse();
+	Some(l)
+}
+
+/**
+ * Finds a move sequence of exactly `length` moves, in the playing order, that ends with the robot `goal.0` on `goal.1`.
+ * The robot may pass the goal on the way, and every move has to move a robot.
+ * A state can be reached again at a later layer, so this costs up to `length` times a plain BFS.
+ */
+pub fn solve_exact(bo: &Board, goal: (usize, Pos), length: usize) -> Option<Vec<Move>> {
+	// `prevs[i]` maps each state reached by exactly `i + 1` moves to the last move of one such sequence.
+	let mut prevs: Vec<HashMap<State, Prev>> = Vec::with_capacity(length);
+	let mut layer = vec![State::init_state(bo)];
+	for _ in 0..length {
+		let mut prev: HashMap<State, Prev> = HashMap::new();
+		let mut next = vec![];
+		for st in &layer {
+			for (ts, m) in st.enumerate_states(bo) {
+				prev.entry(ts.clone()).or_insert_with(|| {
+					next.push(ts);
+					Prev::serialize(&m, &st.robots[m.c])
+				});
+			}
+		}
+		if next.is_empty() {
+			return None;
+		}
+		prevs.push(prev);
+		layer = next;
+	}
+
+	let mut s = layer.into_iter().find(|st| st.robots[goal.0] == goal.1)?;
+	let mut l = vec![];
+	for prev in prevs.iter().rev() {
+		let (m, p) = prev[&s].deserialize();
+		l.push(m);
+		s.robots[m.c] = p;
+	}
+	l.reverse();
+	Some(l)
+}
+
+/**
+ * Whether the teleporters are needed to solve the puzzle in `length` moves,
+ * i.e. the same board without them has no solution that short.
+ */
+pub fn teleporters_matter(bo: &Board, goal: (usize, Pos), length: usize) -> bool {
+	if bo.teleporters.is_empty() {
+		return false;
+	}
+	let mut plain = bo.clone();
+	plain.teleporters.clear();
+	solve(&plain, goal, length).is_none()
+}
+
+/// A puzzle as the bot presents it.
+#[derive(Debug)]
+pub struct Problem {
+	pub board: Board,
+	pub goal: (usize, Pos),
+	/// The optimal moves in the playing order.
+	pub answer: Vec<Move>,
+	/// The strategy which picked the goal, which may be a fallback of the requested one.
+	pub strategy: GoalStrategy,
+}
+
+/// How many boards to try until `BoardOptions::accepts` the puzzle.
+pub(crate) const GENERATION_TRIES: usize = 100;
+
+/// The state of the board RNG for a single seed. Xorshift never leaves the all-zero state, so the halves differ.
+pub fn seed_states(seed: u64) -> [u64; 2] {
+	[seed, !seed]
+}
+
+/**
+ * Generates a board from `seed` and the hardest goal within `depth` moves picked by `options.goal`,
+ * and shuffles the colours with `rng`.
+ * Boards are generated again from derived seeds until `options` accepts the puzzle, up to `GENERATION_TRIES` times.
+ * Fails if the robots don't fit on the board, or if no puzzle is accepted.
+ */
+pub fn generate_problem<R: rand::Rng>(
+	board_h: usize,
+	board_w: usize,
+	wall_num: usize,
+	options: &BoardOptions,
+	depth: u8,
+	seed: [u64; 2],
+	rng: &mut R,
+) -> Result<Problem, String> {
+	let mut board_rng: BoardRng = xorshift::SeedableRng::from_seed(&seed[..]);
+	let mut bo = Board::with_options(board_h, board_w, board_rng, wall_num, options)?;
+	let (mut strategy, mut goal, mut log) = select_goal(depth, &bo, options.goal, rng);
+
+	let mut tries = 0;
+	while tries < GENERATION_TRIES && !options.accepts(&bo, goal, log.len()) {
+		tries += 1;
+		let states: [u64; 2] = [board_rng.gen(), board_rng.gen()];
+		board_rng = xorshift::SeedableRng::from_seed(&states[..]);
+		bo = Board::with_options(board_h, board_w, board_rng, wall_num, options)?;
+		let res = select_goal(depth, &bo, options.goal, rng);
+		strategy = res.0;
+		goal = res.1;
+		log = res.2;
+	}
+	if !options.accepts(&bo, goal, log.len()) {
+		return Err(format!(
+			"no puzzle met the requirements in {} boards",
+			GENERATION_TRIES + 1
+		));
+	}
+
+	let log = log.into_iter().rev().collect();
+	let (goalcolour, answer) = randomize_colours(&mut bo, goal.0, log, rng);
+	let (goalcolour, answer) = strategy.keep_colour(&mut bo, goalcolour, answer);
+	Ok(Problem {
+		board: bo,
+		goal: (goalcolour, goal.1),
+		answer,
+		strategy,
+	})
+}
+
+/**
+ * Shuffles the colours of the robots so that the goal robot isn't always the one found first.
+ * `log` is expected in the playing order, and the permuted goal colour and log are returned.
+ */
+pub fn randomize_colours<R: rand::Rng>(
+	bo: &mut Board,
+	goalcolour: usize,
+	log: Vec<Move>,
+	rng: &mut R,
+) -> (usize, Vec<Move>) {
+	let mut perm: Vec<usize> = (0..bo.robots.len()).collect();
+	perm.shuffle(rng);
+	let mut perminv: Vec<usize> = vec![0; perm.len()];
+	for (i, &p) in perm.iter().enumerate() {
+		perminv[p] = i;
+	}
+
+	let log = log
+		.into_iter()
+		.map(|x| Move {
+			c: perm[x.c],
+			d: x.d,
+		})
+		.collect();
+
+	let copy = bo.robots;
+	for (i, robot) in bo.robots.iter_mut().enumerate() {
+		*robot = copy[perminv[i]];
+	}
+
+	(perm[goalcolour], log)
+}
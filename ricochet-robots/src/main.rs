@@ -1,483 +1,669 @@
-use std::collections::HashMap;
-use std::collections::VecDeque;
 use std::env;
-use std::hash::{Hash, Hasher};
-
-extern crate xorshift;
-use xorshift::{Rng, SeedableRng, Xorshift128};
-type BoardRng = Xorshift128;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 extern crate rand;
-use rand::prelude::SliceRandom;
-
-extern crate time;
-use time::precise_time_ns;
 
-use std::cmp;
-
-extern crate atoi;
-use atoi::atoi;
-
-extern crate itertools;
-use itertools::Itertools;
+extern crate xorshift;
 
-#[derive(PartialEq, Eq, Debug, Clone, Copy)]
-pub struct Pos {
-	y: i8,
-	x: i8,
+extern crate getopts;
+use getopts::{Matches, Options};
+
+extern crate serde_derive;
+use serde_derive::{Deserialize, Serialize};
+
+extern crate serde_json;
+
+extern crate ricochet_robots;
+use ricochet_robots::analysis::{self, StateSpaceReport};
+use ricochet_robots::cost::{solve_with_cost, CostModel};
+use ricochet_robots::design::{optimise_layout, DesignOptions};
+use ricochet_robots::difficulty::{DifficultyReport, DifficultyWeights};
+use ricochet_robots::evaluate::evaluate_answer;
+use ricochet_robots::goal::GoalStrategy;
+use ricochet_robots::notation::Command;
+use ricochet_robots::pool::{BoardRecord, GoalRecord};
+use ricochet_robots::preset::{default_presets, find_preset, load_presets};
+use ricochet_robots::{
+	generate_problem, seed_states, solve, solve_exact, Board, BoardOptions, BoardRng, Move, Pos,
+	Problem, ROBOTS_COUNT,
+};
+
+const USAGE: &str =
+	"usage: ricochet_robot_problem_generator <generate|design|solve|verify|analyze> [options]
+Run a subcommand with --help for its options.";
+
+/// The exit code when no answer is found, or the given answer doesn't solve the puzzle.
+const EXIT_NEGATIVE: i32 = 1;
+
+/// What the caller got wrong. A crash exits with 101 instead.
+enum CliError {
+	/// Bad arguments. Exits with 2.
+	Usage(String),
+	/// An input which can't be read or isn't a valid puzzle, or requirements no puzzle met. Exits with 3.
+	Input(String),
 }
 
-#[derive(Debug)]
-struct WallPos {
-	y: i8,
-	x: i8,
-	d: i8,
+impl CliError {
+	fn exit_code(&self) -> i32 {
+		match self {
+			CliError::Usage(_) => 2,
+			CliError::Input(_) => 3,
+		}
+	}
 }
 
-const ROBOTS_COUNT: usize = 4;
-
-#[derive(Debug)]
-pub struct Board {
-	w: usize,
-	h: usize,
-	walls: Vec<WallPos>,
-	walldist: Vec<Vec<[usize; 4]>>,
-	robots: [Pos; ROBOTS_COUNT],
+/// The puzzle as read by `solve`, `verify` and `analyze`, and as written by `generate --format json`.
+#[derive(Serialize, Deserialize)]
+struct ProblemRecord {
+	board: BoardRecord,
+	goal: Option<GoalRecord>,
+	answer: Option<Vec<Move>>,
+	/// How the goal was picked. Only written by `generate`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	strategy: Option<String>,
 }
 
-const DIRECTIONS: [Pos; 4] = [
-	Pos { y: 1, x: 0 },
-	Pos { y: 0, x: 1 },
-	Pos { y: -1, x: 0 },
-	Pos { y: 0, x: -1 },
-];
-
-impl Board {
-	fn good_board(&mut self) -> bool {
-		let mut gone = vec![vec![false; self.w]; self.h];
-
-		fn dfs<'a>(gone: &'a mut Vec<Vec<bool>>, self_: &'a Board, y: usize, x: usize) -> usize {
-			//println!("{} {}",y,x);
-			if gone[y][x] {
-				return 0;
-			}
-			gone[y][x] = true;
-			let mut res = 1;
-			for i in 0..4 {
-				if self_.walldist[y][x][i] <= 0 {
-					continue;
-				}
-				let ty = (y as i8 + DIRECTIONS[i].y) as usize;
-				let tx = (x as i8 + DIRECTIONS[i].x) as usize;
-				if ty < self_.h && tx < self_.w {
-					res += dfs(gone, self_, ty, tx);
-				}
-			}
-			return res;
-		}
-
-		let cn = dfs(&mut gone, self, 0, 0);
-		//println!("{}",cn);
-		if cn != self.h * self.w {
-			// all cells aren't connected
-			return false;
+fn main() {
+	let args: Vec<String> = env::args().skip(1).collect();
+	let res = match args.first().map(String::as_str) {
+		Some("generate") => generate(&args[1..]),
+		Some("design") => design(&args[1..]),
+		Some("solve") => solve_command(&args[1..]),
+		Some("verify") => verify(&args[1..]),
+		Some("analyze") => analyze(&args[1..]),
+		Some("-h") | Some("--help") => {
+			println!("{}", USAGE);
+			Ok(0)
 		}
-
-		//println!("{:?}",self.board);
-
-		for y in 0..self.h {
-			for x in 0..self.w {
-				let mut d = 0;
-				for i in 0..4 {
-					if self.walldist[y][x][i] > 0 {
-						d += 1;
-					}
-				}
-				//println!("{}",d);
-				if d < 2 {
-					// This cell is not interesting.
-					return false;
-				}
-			}
+		Some(command) => Err(CliError::Usage(format!(
+			"unknown subcommand {:?}\n{}",
+			command, USAGE
+		))),
+		None => Err(CliError::Usage(USAGE.to_string())),
+	};
+	match res {
+		Ok(code) => process::exit(code),
+		Err(e) => {
+			let (CliError::Usage(message) | CliError::Input(message)) = &e;
+			eprintln!("error: {}", message);
+			process::exit(e.exit_code());
 		}
-
-		return true;
 	}
+}
 
-	fn init(&mut self, mut rng: BoardRng, wall_num: usize) {
-		self.walldist = vec![vec![[0; 4]; self.w]; self.h];
-
-		//println!("{} {} {} {}",self.board.len(), self.h, self.board[0].len(), self.w);
-		println!("{}", rng.gen_range(0, 1000));
-		for y in 0..self.h {
-			for x in 0..self.w {
-				self.walldist[y][x] = [self.h - 1 - y, self.w - 1 - x, y, x];
-			}
-		}
-
-		for _ in 0..wall_num {
-			let mem_walldist = self.walldist.clone();
-			let mut add_walls = vec![];
-			let cy = rng.gen_range(0, self.h);
-			let cx = rng.gen_range(0, self.w);
-			{
-				let y = cy + rng.gen_range(0, 2);
-				let x = cx;
-				if 0 < y && y < self.h {
-					add_walls.push(WallPos {
-						y: y as i8,
-						x: x as i8,
-						d: 0,
-					});
-
-					for ty in 0..y {
-						self.walldist[ty][x][0] = cmp::min(y - 1 - ty, self.walldist[ty][x][0]);
-					}
-					for ty in y..self.h {
-						self.walldist[ty][x][2] = cmp::min(ty - y, self.walldist[ty][x][2]);
-					}
-				}
-			}
-			{
-				let y = cy;
-				let x = cx + rng.gen_range(0, 2);
-				if 0 < x && x < self.w {
-					add_walls.push(WallPos {
-						y: y as i8,
-						x: x as i8,
-						d: 1,
-					});
-					for tx in 0..x {
-						self.walldist[y][tx][1] = cmp::min(x - 1 - tx, self.walldist[y][tx][1]);
-					}
-					for tx in x..self.w {
-						self.walldist[y][tx][3] = cmp::min(tx - x, self.walldist[y][tx][3]);
-					}
-				}
-			}
-
-			if self.good_board() {
-				println!("add walls {:?}", add_walls);
-				self.walls.append(&mut add_walls);
-			} else {
-				self.walldist = mem_walldist;
-			}
-		}
-
-		let mut i = 0;
-		while i < 4 {
-			let tp = Pos {
-				y: rng.gen_range(0, self.h) as i8,
-				x: rng.gen_range(0, self.w) as i8,
-			};
-			let mut ok = true;
-			for j in 0..i {
-				ok &= tp != self.robots[j];
-			}
-			if ok {
-				self.robots[i] = tp;
-				i += 1;
-			}
-		}
+/// Parses the options, or returns `None` after printing the help.
+fn parse_args(
+	opts: &mut Options,
+	args: &[String],
+	brief: &str,
+) -> Result<Option<Matches>, CliError> {
+	opts.optflag("h", "help", "print this help");
+	let matches = opts
+		.parse(args)
+		.map_err(|e| CliError::Usage(format!("{}\n{}", e, opts.usage(brief))))?;
+	if matches.opt_present("help") {
+		print!("{}", opts.usage(brief));
+		return Ok(None);
 	}
-	pub fn new(board_h: usize, board_w: usize, rng: BoardRng, wall_num: usize) -> Board {
-		let mut res = Board {
-			w: board_w,
-			h: board_h,
-			walls: vec![],
-			walldist: vec![],
-			robots: [Pos { y: 0, x: 0 }; ROBOTS_COUNT],
-		};
-		res.init(rng, wall_num);
-		return res;
+	if !matches.free.is_empty() {
+		return Err(CliError::Usage(format!(
+			"unexpected argument {:?}",
+			matches.free[0]
+		)));
 	}
+	Ok(Some(matches))
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct Move {
-	c: usize,
-	d: usize,
+fn parse_opt<T: FromStr>(matches: &Matches, name: &str) -> Result<Option<T>, CliError>
+where
+	T::Err: ToString,
+{
+	match matches.opt_str(name) {
+		Some(value) => value.parse().map(Some).map_err(|e: T::Err| {
+			CliError::Usage(format!(
+				"invalid value {:?} for --{}: {}",
+				value,
+				name,
+				e.to_string()
+			))
+		}),
+		None => Ok(None),
+	}
 }
 
-#[derive(Clone, PartialEq, Eq)]
-struct State {
-	robots: [Pos; ROBOTS_COUNT],
+fn require<T>(value: Option<T>, name: &str) -> Result<T, CliError> {
+	value.ok_or_else(|| CliError::Usage(format!("--{} is required", name)))
 }
 
-impl State {
-	pub fn init_state(bo: &Board) -> State {
-		State {
-			robots: bo.robots.clone(),
-		}
-	}
+/// The options describing the board, shared by `generate` and `design`.
+fn add_board_opts(opts: &mut Options) {
+	opts.optopt(
+		"p",
+		"preset",
+		"start from the preset (baby, super or hyper in the bundled file)",
+		"NAME",
+	);
+	opts.optopt(
+		"",
+		"presets",
+		"the presets file (defaults to the bundled presets.json)",
+		"PATH",
+	);
+	opts.optopt(
+		"d",
+		"depth",
+		"the target length of the answer, capped at 255 (required without --preset)",
+		"N",
+	);
+	opts.optopt(
+		"",
+		"height",
+		"the number of rows (required without --preset)",
+		"H",
+	);
+	opts.optopt(
+		"",
+		"width",
+		"the number of columns (required without --preset)",
+		"W",
+	);
+	opts.optopt(
+		"",
+		"walls",
+		"the number of walls (required without --preset)",
+		"N",
+	);
+	opts.optopt(
+		"",
+		"policy",
+		"random, l-shaped, symmetric, density:<r> or edge-stubs:<n>",
+		"POLICY",
+	);
+	opts.optopt(
+		"",
+		"shape",
+		"rectangle, central-block, cross or donut",
+		"SHAPE",
+	);
+	opts.optopt("", "holes", "the number of holes", "N");
+	opts.optflag("", "torus", "wrap around the edges");
+	opts.optopt(
+		"",
+		"teleporters",
+		"the number of pairs of teleporter pads",
+		"N",
+	);
+	opts.optopt("", "sticky", "the number of sticky cells", "N");
+	opts.optopt("", "ice", "the number of ice cells", "N");
+	opts.optopt(
+		"",
+		"min-length",
+		"retry until the answer has at least this many moves",
+		"N",
+	);
+	opts.optopt(
+		"",
+		"min-robots",
+		"retry until every optimal solution moves this many robots",
+		"N",
+	);
+	opts.optflag(
+		"",
+		"require-blocker",
+		"retry until every optimal solution stops the goal robot against another robot",
+	);
+	opts.optopt(
+		"",
+		"goal",
+		"how to pick the goal: last (default), uniform, corner or robot:<colour>",
+		"STRATEGY",
+	);
+	opts.optopt(
+		"",
+		"seed",
+		"the seed of the board (defaults to the current time)",
+		"N",
+	);
+	opts.optopt("", "format", "debug (default) or json", "FORMAT");
+}
 
-	fn move_to(&self, board: &Board, robot_index: usize, robot_dir: usize) -> Option<State> {
-		let dir = &DIRECTIONS[robot_dir];
-		let mut p = self.robots[robot_index];
-		let mut mind = board.walldist[p.y as usize][p.x as usize][robot_dir] as i8;
-		//removing "as i8" by changing type of walldist doesn't make well difference.
-
-		// if mind == 0 { return None } //pruning with little (0.2~3sec) speedup.
-		/*
-		if robot_dir == 2 {
-			for j in 0..4 {
-				if j != robot_index {
-					if self.robots[j].x == p.x && self.robots[j].y < p.y {
-						mind = cmp::min(mind,p.y - self.robots[j].y - 1);
-					}
-				}
-			}
-		} else if robot_dir == 0 {
-			for j in 0..4 {
-				if j != robot_index {
-					if self.robots[j].x == p.x && self.robots[j].y > p.y {
-						mind = cmp::min(mind,self.robots[j].y - p.y - 1);
-					}
-				}
-			}
-		} else {
-			for j in 0..4 {
-				if j != robot_index {
-					let dx = self.robots[j].x - p.x;
-					if dx.signum() == dir.x.signum() && self.robots[j].y == p.y {
-						mind = cmp::min(mind,dx.abs()-1);
-					}
-				}
-			}
-		}
-		//unloling also has little speedup (0.2~0.3 sec)
-		*/
-		for j in 0..4 {
-			if j != robot_index {
-				let dx = self.robots[j].x - p.x;
-				let dy = self.robots[j].y - p.y;
-				if dx.signum() == dir.x.signum() && dy.signum() == dir.y.signum() {
-					if dx.signum() == 0 {
-						mind = cmp::min(mind, dy.abs() - 1);
-					} else {
-						mind = cmp::min(mind, dx.abs() - 1);
-					}
-				}
-			}
-		}
+struct BoardSpec {
+	depth: u8,
+	board_h: usize,
+	board_w: usize,
+	wall_num: usize,
+	options: BoardOptions,
+	seed: u64,
+	json: bool,
+}
 
-		if mind == 0 {
-			return None;
-		}
+fn board_spec(matches: &Matches) -> Result<BoardSpec, CliError> {
+	let presets = match matches.opt_str("presets") {
+		Some(path) => load_presets(path).map_err(CliError::Input)?,
+		None => default_presets(),
+	};
+	let preset = match matches.opt_str("preset") {
+		Some(name) => Some(
+			find_preset(&presets, &name)
+				.ok_or_else(|| CliError::Usage(format!("unknown preset {:?}", name)))?,
+		),
+		None => None,
+	};
 
-		p = Pos {
-			y: p.y + dir.y * mind,
-			x: p.x + dir.x * mind,
-		};
+	// The bot asks for 1000 moves to get the longest answer on the board.
+	// `bfs` stops once it runs out of states, so 255 moves mean the same.
+	let depth: Option<u64> = parse_opt(matches, "depth")?;
+	let depth = depth.map(|d| d.min(u8::MAX as u64) as u8);
+	let depth = require(depth.or_else(|| preset.map(|p| p.depth)), "depth")?;
+	let board_h: Option<usize> = parse_opt(matches, "height")?;
+	let board_h = require(board_h.or_else(|| preset.map(|p| p.h)), "height")?;
+	let board_w: Option<usize> = parse_opt(matches, "width")?;
+	let board_w = require(board_w.or_else(|| preset.map(|p| p.w)), "width")?;
+	let wall_num: Option<usize> = parse_opt(matches, "walls")?;
+	let wall_num = require(wall_num.or_else(|| preset.map(|p| p.wall_num)), "walls")?;
+
+	// The flags override the options of the preset.
+	let mut options = preset.map_or_else(BoardOptions::default, |p| p.options);
+	if let Some(policy) = parse_opt(matches, "policy")? {
+		options.policy = policy;
+	}
+	if let Some(shape) = parse_opt(matches, "shape")? {
+		options.shape = shape;
+	}
+	if let Some(hole_num) = parse_opt(matches, "holes")? {
+		options.hole_num = hole_num;
+	}
+	options.torus |= matches.opt_present("torus");
+	if let Some(teleporter_num) = parse_opt(matches, "teleporters")? {
+		options.teleporter_num = teleporter_num;
+	}
+	if let Some(sticky_num) = parse_opt(matches, "sticky")? {
+		options.sticky_num = sticky_num;
+	}
+	if let Some(ice_num) = parse_opt(matches, "ice")? {
+		options.ice_num = ice_num;
+	}
+	if let Some(min_length) = parse_opt(matches, "min-length")? {
+		options.min_length = min_length;
+	}
+	// A shorter depth than the preset allows asks for that many moves.
+	options.min_length = options.min_length.min(depth as usize);
+	if let Some(min_robots) = parse_opt(matches, "min-robots")? {
+		options.min_robots = min_robots;
+	}
+	options.require_blocker |= matches.opt_present("require-blocker");
+	if let Some(goal) = parse_opt(matches, "goal")? {
+		options.goal = goal;
+	}
+	options
+		.check(board_h, board_w, wall_num)
+		.map_err(CliError::Usage)?;
+	let json = match matches.opt_str("format").as_deref() {
+		None | Some("debug") => false,
+		Some("json") => true,
+		Some(format) => return Err(CliError::Usage(format!("unknown format {:?}", format))),
+	};
+	let seed = match parse_opt(matches, "seed")? {
+		Some(seed) => seed,
+		None => SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.unwrap()
+			.as_nanos() as u64,
+	};
+	Ok(BoardSpec {
+		depth,
+		board_h,
+		board_w,
+		wall_num,
+		options,
+		seed,
+		json,
+	})
+}
 
-		let mut res = State {
-			robots: self.robots.clone(),
+/// Prints the puzzle, and the strategy which picked its goal.
+fn print_problem(problem: &Problem, json: bool) {
+	let Problem {
+		board: bo,
+		goal: (goalcolour, goalpos),
+		answer: log,
+		strategy,
+	} = problem;
+	if json {
+		let record = ProblemRecord {
+			board: BoardRecord::new(bo),
+			goal: Some(GoalRecord {
+				colour: *goalcolour,
+				y: goalpos.y,
+				x: goalpos.x,
+			}),
+			answer: Some(log.clone()),
+			strategy: Some(strategy.to_string()),
 		};
-		res.robots[robot_index] = p;
-		Some(res)
+		println!("{}", serde_json::to_string(&record).unwrap());
+	} else {
+		println!("goal strategy: {}", strategy);
+		// The bot reads the last four lines.
+		println!("{:?}", bo);
+		println!("{:?}", goalcolour);
+		println!("{:?}", goalpos);
+		println!("{:?}", log);
 	}
+}
 
-	fn enumerate_states(&self, board: &Board) -> Vec<(State, Move)> {
-		let mut res = Vec::with_capacity(16);
-		for i in 0..self.robots.len() {
-			for j in 0..4 {
-				if let Some(ts) = self.move_to(board, i, j) {
-					res.push((ts, Move { c: i, d: j }));
-				}
-			}
+fn generate(args: &[String]) -> Result<i32, CliError> {
+	let mut opts = Options::new();
+	add_board_opts(&mut opts);
+	let matches = match parse_args(
+		&mut opts,
+		args,
+		"usage: ricochet_robot_problem_generator generate [options]",
+	)? {
+		Some(matches) => matches,
+		None => return Ok(0),
+	};
+	let spec = board_spec(&matches)?;
+
+	let problem = generate_problem(
+		spec.board_h,
+		spec.board_w,
+		spec.wall_num,
+		&spec.options,
+		spec.depth,
+		seed_states(spec.seed),
+		&mut rand::thread_rng(),
+	)
+	.map_err(CliError::Input)?;
+	print_problem(&problem, spec.json);
+	Ok(0)
+}
+
+fn design(args: &[String]) -> Result<i32, CliError> {
+	let mut opts = Options::new();
+	add_board_opts(&mut opts);
+	opts.optopt(
+		"",
+		"iterations",
+		"the number of changes to try (default 1000)",
+		"N",
+	);
+	opts.optopt(
+		"",
+		"temperature",
+		"the initial temperature of simulated annealing, 0 for hill climbing (default 1)",
+		"T",
+	);
+	opts.optopt("", "min-walls", "keep at least this many walls", "N");
+	opts.optopt(
+		"",
+		"max-walls",
+		"keep at most this many walls (default twice --walls)",
+		"N",
+	);
+	let matches = match parse_args(
+		&mut opts,
+		args,
+		"usage: ricochet_robot_problem_generator design [options]
+Starts from a generated board and changes its walls and robots to make the puzzle deeper.",
+	)? {
+		Some(matches) => matches,
+		None => return Ok(0),
+	};
+	let spec = board_spec(&matches)?;
+	let mut design_options = DesignOptions {
+		target: spec.depth,
+		max_walls: spec.wall_num * 2,
+		..DesignOptions::default()
+	};
+	if let Some(iterations) = parse_opt(&matches, "iterations")? {
+		design_options.iterations = iterations;
+	}
+	if let Some(temperature) = parse_opt::<f64>(&matches, "temperature")? {
+		if temperature.is_nan() || temperature < 0.0 {
+			return Err(CliError::Usage(format!(
+				"the temperature must not be negative, got {}",
+				temperature
+			)));
 		}
-		return res;
+		design_options.temperature = temperature;
+	}
+	if spec.options.goal != GoalStrategy::Last {
+		return Err(CliError::Usage(
+			"design always picks the goal which bfs reaches last".to_string(),
+		));
+	}
+	if let Some(min_walls) = parse_opt(&matches, "min-walls")? {
+		design_options.min_walls = min_walls;
 	}
+	if let Some(max_walls) = parse_opt(&matches, "max-walls")? {
+		design_options.max_walls = max_walls;
+	}
+
+	let board_rng: BoardRng = xorshift::SeedableRng::from_seed(&seed_states(spec.seed)[..]);
+	let bo = Board::with_options(
+		spec.board_h,
+		spec.board_w,
+		board_rng,
+		spec.wall_num,
+		&spec.options,
+	)
+	.map_err(CliError::Input)?;
+	// A different stream from the one which made the board.
+	let mut rng: BoardRng = xorshift::SeedableRng::from_seed(&[!spec.seed, spec.seed][..]);
+	let design = optimise_layout(bo, &design_options, &mut rng);
+	eprintln!(
+		"{} moves after {} evaluated changes",
+		design.answer.len(),
+		design.evaluated
+	);
+	let problem = Problem {
+		board: design.board,
+		goal: design.goal,
+		answer: design.answer,
+		strategy: GoalStrategy::Last,
+	};
+	print_problem(&problem, spec.json);
+	Ok(0)
 }
 
-impl Hash for State {
-	fn hash<H: Hasher>(&self, state: &mut H) {
-		//Surprisingly, this makes program very fast!
-		//:waiwai:
-		let mut bits: u64 = 0;
-		for i in 0..ROBOTS_COUNT {
-			let p = self.robots[i];
-			bits |= (((p.y as u64) << 8) | (p.x as u64)) << (i * 16);
+/// Reads the puzzle from `--input`, or from the standard input.
+fn read_problem(matches: &Matches) -> Result<(ProblemRecord, Board), CliError> {
+	let text = match matches.opt_str("input") {
+		Some(path) if path != "-" => fs::read_to_string(&path)
+			.map_err(|e| CliError::Input(format!("can't read {}: {}", path, e)))?,
+		_ => {
+			let mut text = String::new();
+			io::stdin()
+				.read_to_string(&mut text)
+				.map_err(|e| CliError::Input(format!("can't read the standard input: {}", e)))?;
+			text
 		}
-		bits.hash(state);
-	}
+	};
+	let record: ProblemRecord = serde_json::from_str(&text)
+		.map_err(|e| CliError::Input(format!("invalid puzzle: {}", e)))?;
+	let bo = record.board.to_board().map_err(CliError::Input)?;
+	Ok((record, bo))
 }
 
-/**
- * Its internal representation is like below:
- *
- *                  0b_00000000_00000000
- *     robot_index     ^^
- *       robot_dir       ^^
- *          prev_y         ^^^^ ^^
- *          prev_x                ^^^^^^
- *
- * Assume that
- *   - the number of robots < 4 and
- *   - the width and height of the board < 64.
- * Making the data compact increases speed a little. (ura)
- */
-struct Prev(u16);
-
-impl Prev {
-	/**
-	 * The initial state has no previous state, so use this dummy value.
-	 * However, there is no mechanism to check if it is a dummy or not,
-	 * so please check if the state is the initial state or not.
-	 */
-	fn dummy() -> Self {
-		Prev(!0)
+fn goal_of(record: &ProblemRecord) -> Result<(usize, Pos), CliError> {
+	let goal = record
+		.goal
+		.as_ref()
+		.ok_or_else(|| CliError::Input("the puzzle has no goal".to_string()))?;
+	let (h, w) = (record.board.h as i8, record.board.w as i8);
+	if goal.colour >= ROBOTS_COUNT || !(0..h).contains(&goal.y) || !(0..w).contains(&goal.x) {
+		return Err(CliError::Input(format!("invalid goal {:?}", goal)));
 	}
+	Ok((
+		goal.colour,
+		Pos {
+			y: goal.y,
+			x: goal.x,
+		},
+	))
+}
 
-	fn serialize(m: &Move, p: &Pos) -> Self {
-		let prev = ((m.c as u16) << 14) | ((m.d as u16) << 12) | ((p.y as u16) << 6) | (p.x as u16);
-		Prev(prev)
-	}
+fn add_input_opts(opts: &mut Options) {
+	opts.optopt(
+		"i",
+		"input",
+		"the puzzle as JSON (defaults to the standard input)",
+		"PATH",
+	);
+	opts.optopt(
+		"",
+		"max-depth",
+		"give up after this many moves (default 30)",
+		"N",
+	);
+}
 
-	fn deserialize(&self) -> (Move, Pos) {
-		let robot_index = (self.0 >> 14) as usize;
-		let robot_dir = ((self.0 >> 12) & 0b11) as usize;
-		let prev_y = ((self.0 >> 6) & 0b111111) as i8;
-		let prev_x = (self.0 & 0b111111) as i8;
-		(
-			Move {
-				c: robot_index,
-				d: robot_dir,
-			},
-			Pos {
-				y: prev_y,
-				x: prev_x,
-			},
-		)
-	}
+fn max_depth(matches: &Matches) -> Result<usize, CliError> {
+	Ok(parse_opt(matches, "max-depth")?.unwrap_or(30))
 }
 
-pub fn bfs<'a, 'b>(target: u8, bo: &'a Board) -> ((usize, Pos), Vec<Move>) {
-	let init = State::init_state(&bo);
-	let mut last_state = init.clone();
-	let mut goal = (0, init.robots[0]);
-
-	let mut prev: HashMap<State, Prev> = HashMap::new();
-	prev.insert(init.clone(), Prev::dummy());
-
-	let mut que = VecDeque::new();
-	let mut depth = 0;
-	que.push_back(Some(init.clone()));
-	que.push_back(None);
-
-	let mut found = vec![vec![[false; ROBOTS_COUNT]; bo.w]; bo.h];
-	let mut found_count = 0;
-	let max_pattern_num = bo.h * bo.w * bo.robots.len();
-
-	let mut dnum = 1;
-	while let Some(st) = que.pop_front() {
-		match st {
-			Some(st) => {
-				last_state = st.clone();
-				dnum += 1;
-				//println!("{:?}",st.robots);
-				let mut ok = false;
-				for i in 0..st.robots.len() {
-					let p = st.robots[i];
-					if !found[p.y as usize][p.x as usize][i] {
-						//println!("{} {} {} : {} ",p.y,p.x,i,depth);
-						found[p.y as usize][p.x as usize][i] = true;
-						found_count += 1;
-						goal = (i, p);
-						if depth >= target || found_count >= max_pattern_num {
-							ok = true;
-							break;
-						}
-					}
-				}
-				if ok {
-					break;
-				}
-				for (ts, m) in st.enumerate_states(&bo) {
-					// kcz-san and satos-san say that performing `push_back` here
-					// decreases speed, but this is necessary for path reconstruction.
-					// However, using `entry` instead of `contains_key` and `insert`
-					// increases speed a bit. (ura)
-					prev.entry(ts.clone()).or_insert_with(|| {
-						que.push_back(Some(ts));
-						let p = st.robots[m.c];
-						Prev::serialize(&m, &p)
-					});
-				}
-			}
-			None => {
-				depth += 1;
-				if depth > target {
-					break;
-				}
-				println!("{} {}", depth, dnum);
-				dnum = 0;
-				que.push_back(None);
-			}
-		}
+fn solve_command(args: &[String]) -> Result<i32, CliError> {
+	let mut opts = Options::new();
+	add_input_opts(&mut opts);
+	opts.optopt(
+		"",
+		"cost",
+		"moves, selections or weighted:<goal>:<other>, to report the costs",
+		"MODEL",
+	);
+	opts.optopt(
+		"",
+		"exact",
+		"find an answer of exactly N moves, even if a shorter one exists",
+		"N",
+	);
+	let matches = match parse_args(
+		&mut opts,
+		args,
+		"usage: ricochet_robot_problem_generator solve [options]",
+	)? {
+		Some(matches) => matches,
+		None => return Ok(0),
+	};
+	let max_depth = max_depth(&matches)?;
+	let model: Option<CostModel> = parse_opt(&matches, "cost")?;
+	let exact: Option<usize> = parse_opt(&matches, "exact")?;
+	if model.is_some() && exact.is_some() {
+		return Err(CliError::Usage(
+			"--cost and --exact can't be used together".to_string(),
+		));
 	}
+	let (record, bo) = read_problem(&matches)?;
+	let goal = goal_of(&record)?;
+
+	let found = match (model, exact) {
+		(_, Some(length)) => {
+			let answer = solve_exact(&bo, goal, length);
+			println!("{}", serde_json::to_string(&answer).unwrap());
+			answer.is_some()
+		}
+		(Some(model), None) => {
+			let report = solve_with_cost(&bo, goal, &model, max_depth as u32);
+			println!("{}", serde_json::to_string(&report).unwrap());
+			report.is_some()
+		}
+		(None, None) => {
+			let answer = solve(&bo, goal, max_depth);
+			println!("{}", serde_json::to_string(&answer).unwrap());
+			answer.is_some()
+		}
+	};
+	Ok(if found { 0 } else { EXIT_NEGATIVE })
+}
 
-	// path reconstruction
-	let mut l = vec![];
-	let mut s = last_state;
-	while s != init {
-		let (m, p) = prev[&s].deserialize();
-		l.push(m);
-		s.robots[m.c] = p;
+fn verify(args: &[String]) -> Result<i32, CliError> {
+	let mut opts = Options::new();
+	add_input_opts(&mut opts);
+	opts.optopt(
+		"a",
+		"answer",
+		"the answer as players type it, like 赤下右青上まで, instead of the one in the input",
+		"TEXT",
+	);
+	let matches = match parse_args(
+		&mut opts,
+		args,
+		"usage: ricochet_robot_problem_generator verify [options]",
+	)? {
+		Some(matches) => matches,
+		None => return Ok(0),
+	};
+	let max_depth = max_depth(&matches)?;
+	let command: Option<Command> = parse_opt(&matches, "answer")?;
+	let (record, bo) = read_problem(&matches)?;
+	let goal = goal_of(&record)?;
+	let answer = match &command {
+		Some(command) => &command.moves,
+		None => record
+			.answer
+			.as_ref()
+			.ok_or_else(|| CliError::Input("the puzzle has no answer to verify".to_string()))?,
+	};
+	if let Some(m) = answer.iter().find(|m| m.c >= ROBOTS_COUNT || m.d >= 4) {
+		return Err(CliError::Input(format!("invalid move {:?}", m)));
 	}
 
-	return (goal, l);
+	let optimal_length = solve(&bo, goal, max_depth)
+		.ok_or_else(|| {
+			CliError::Input(format!(
+				"the puzzle has no answer within {} moves",
+				max_depth
+			))
+		})?
+		.len();
+	let evaluation = evaluate_answer(&bo, goal, optimal_length, answer);
+	println!("{}", serde_json::to_string(&evaluation).unwrap());
+	Ok(if evaluation.solved { 0 } else { EXIT_NEGATIVE })
 }
 
-fn main() {
-	let args: Vec<String> = env::args().collect();
-	let (depth, board_h, board_w, wall_num) = match args[1..5]
-		.into_iter()
-		.map(|x| atoi(x.as_bytes()))
-		.tuples()
-		.next()
-	{
-		Some((Some(a), Some(b), Some(c), Some(d))) => (a, b, c, d),
-		v => panic!(
-			"invalid argument. expect \"depth board_h board_w wall_num\", got {:?}.",
-			v
-		),
-	};
+#[derive(Serialize)]
+struct AnalysisOutput {
+	analysis: StateSpaceReport,
+	/// Only for puzzles with a goal.
+	difficulty: Option<DifficultyReport>,
+}
 
-	let now = precise_time_ns();
-	let states = [now, now];
-	let stdrng = SeedableRng::from_seed(&states[..]);
-	let mut bo = Board::new(board_h, board_w, stdrng, wall_num);
-	let ((mut goalcolour, goalpos), mut log) = bfs(depth as u8, &bo);
-
-	//randomize colour
-	let mut rng = rand::thread_rng();
-	let mut perm: Vec<usize> = (0..bo.robots.len()).collect();
-	perm.shuffle(&mut rng);
-	let mut perminv: Vec<usize> = vec![0; perm.len()];
-	for i in 0..perm.len() {
-		perminv[perm[i]] = i;
+fn analyze(args: &[String]) -> Result<i32, CliError> {
+	let mut opts = Options::new();
+	add_input_opts(&mut opts);
+	let matches = match parse_args(
+		&mut opts,
+		args,
+		"usage: ricochet_robot_problem_generator analyze [options]",
+	)? {
+		Some(matches) => matches,
+		None => return Ok(0),
+	};
+	let max_depth = max_depth(&matches)?;
+	let (record, bo) = read_problem(&matches)?;
+	if record.board.h * record.board.w > analysis::MAX_CELLS {
+		return Err(CliError::Usage(format!(
+			"can't analyze boards of more than {} cells",
+			analysis::MAX_CELLS
+		)));
 	}
-
-	goalcolour = perm[goalcolour];
-	log = log
-		.into_iter()
-		.map(|x| Move {
-			c: perm[x.c],
-			d: x.d,
-		})
-		.rev()
-		.collect();
-
-	{
-		let copy = bo.robots;
-		for i in 0..ROBOTS_COUNT {
-			bo.robots[i] = copy[perminv[i]];
+	let difficulty = match record.goal {
+		Some(_) => {
+			let goal = goal_of(&record)?;
+			solve(&bo, goal, max_depth).map(|answer| {
+				DifficultyReport::new(&bo, goal, answer.len(), &DifficultyWeights::default())
+			})
 		}
-	}
-
-	println!("{:?}", bo);
-	println!("{:?}", goalcolour);
-	println!("{:?}", goalpos);
-	println!("{:?}", log);
+		None => None,
+	};
+	let output = AnalysisOutput {
+		analysis: StateSpaceReport::new(&bo),
+		difficulty,
+	};
+	println!("{}", serde_json::to_string(&output).unwrap());
+	Ok(0)
 }
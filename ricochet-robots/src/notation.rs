@@ -0,0 +1,142 @@
+use std::fmt;
+use std::str::FromStr;
+
+extern crate serde_derive;
+use serde_derive::Serialize;
+
+use crate::Move;
+
+/// The names of the robots as the bot writes them, by colour index.
+pub const COLOUR_NAMES: [char; 4] = ['赤', '緑', '青', '黄'];
+
+/// The names of the directions as the bot writes them, by direction index.
+pub const DIRECTION_NAMES: [char; 4] = ['下', '右', '上', '左'];
+
+/// The suffix of an answer which doesn't claim to be the shortest.
+pub const MADE: &str = "まで";
+
+/// Other letters players type: `rgby` for the colours, and `sdwa` or `jlkh` for the directions.
+const COLOUR_LETTERS: [char; 4] = ['r', 'g', 'b', 'y'];
+const DIRECTION_LETTERS: [[char; 4]; 2] = [['s', 'd', 'w', 'a'], ['j', 'l', 'k', 'h']];
+
+/// An answer typed by a player, like `赤下右青上まで`. The same grammar as `iscommand` in board.ts.
+#[derive(Debug, Clone, Serialize)]
+pub struct Command {
+	pub moves: Vec<Move>,
+	/// Whether the answer ends with `まで`.
+	#[serde(rename = "isMADE")]
+	pub made: bool,
+}
+
+/// Where and why a command doesn't parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+	/// The index of the offending character, counted in characters rather than bytes.
+	pub position: usize,
+	pub expected: &'static str,
+	/// `None` at the end of the text.
+	pub found: Option<char>,
+}
+
+impl fmt::Display for ParseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self.found {
+			Some(c) => write!(
+				f,
+				"expected {} at character {}, found {:?}",
+				self.expected,
+				self.position + 1,
+				c
+			),
+			None => write!(f, "expected {} at the end", self.expected),
+		}
+	}
+}
+
+fn colour(c: char) -> Option<usize> {
+	COLOUR_NAMES
+		.iter()
+		.position(|&n| n == c)
+		.or_else(|| COLOUR_LETTERS.iter().position(|&n| n == c))
+}
+
+fn direction(c: char) -> Option<usize> {
+	std::iter::once(&DIRECTION_NAMES)
+		.chain(&DIRECTION_LETTERS)
+		.find_map(|names| names.iter().position(|&n| n == c))
+}
+
+/// Parses a robot colour followed by its directions, repeated, and optionally `まで`.
+pub fn parse_command(s: &str) -> Result<Command, ParseError> {
+	let chars: Vec<char> = s.chars().collect();
+	let made: Vec<char> = MADE.chars().collect();
+	let error = |position: usize, expected: &'static str| ParseError {
+		position,
+		expected,
+		found: chars.get(position).cloned(),
+	};
+
+	let mut moves = vec![];
+	let mut i = 0;
+	loop {
+		let c = match chars.get(i).cloned().and_then(colour) {
+			Some(c) => c,
+			None if moves.is_empty() => return Err(error(i, "a colour")),
+			None => return Err(error(i, "a colour, a direction or まで")),
+		};
+		i += 1;
+		match chars.get(i).cloned().and_then(direction) {
+			Some(_) => {}
+			None => return Err(error(i, "a direction")),
+		}
+		while let Some(d) = chars.get(i).cloned().and_then(direction) {
+			moves.push(Move { c, d });
+			i += 1;
+		}
+		if i == chars.len() {
+			return Ok(Command { moves, made: false });
+		}
+		if chars[i..].starts_with(&made) {
+			i += made.len();
+			if i < chars.len() {
+				return Err(error(i, "the end after まで"));
+			}
+			return Ok(Command { moves, made: true });
+		}
+	}
+}
+
+impl FromStr for Command {
+	type Err = ParseError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		parse_command(s)
+	}
+}
+
+/// Writes the moves in the shortest form `parse_command` reads, with `まで` if `made`.
+impl fmt::Display for Command {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let mut last = None;
+		for m in &self.moves {
+			if last != Some(m.c) {
+				write!(f, "{}", COLOUR_NAMES[m.c])?;
+				last = Some(m.c);
+			}
+			write!(f, "{}", DIRECTION_NAMES[m.d])?;
+		}
+		if self.made {
+			write!(f, "{}", MADE)?;
+		}
+		Ok(())
+	}
+}
+
+/// Writes the moves like `logstringfy` in board.ts, e.g. `赤下,青左`.
+pub fn format_moves(moves: &[Move]) -> String {
+	moves
+		.iter()
+		.map(|m| format!("{}{}", COLOUR_NAMES[m.c], DIRECTION_NAMES[m.d]))
+		.collect::<Vec<_>>()
+		.join(",")
+}
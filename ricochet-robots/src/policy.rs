@@ -0,0 +1,214 @@
+use std::str::FromStr;
+
+use xorshift::Rng;
+
+use crate::{Board, BoardRng, Pos, WallPos};
+
+/// How `Board::with_policy` lays out the walls.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WallPolicy {
+	/// Tries `wall_num` random pairs of walls around a cell. Pairs on the border lose one of the walls.
+	RandomPairs,
+	/// Puts `wall_num` L-shaped corners on inner cells like the physical game.
+	/// No two corners touch each other.
+	LShaped,
+	/// Random pairs, each added together with its 180-degree rotation.
+	Symmetric,
+	/// Adds single walls until `density` of the inner edges have walls. `wall_num` is ignored.
+	Density { density: f64 },
+	/// Puts `stubs` walls sticking out of each side of the border, then `wall_num` L-shaped corners.
+	EdgeStubs { stubs: usize },
+}
+
+impl FromStr for WallPolicy {
+	type Err = String;
+
+	/// Parses `random`, `l-shaped`, `symmetric`, `density:<ratio>` or `edge-stubs:<count>`.
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut it = s.splitn(2, ':');
+		let name = it.next().unwrap_or("");
+		let arg = it.next();
+		match (name, arg) {
+			("random", None) => Ok(WallPolicy::RandomPairs),
+			("l-shaped", None) => Ok(WallPolicy::LShaped),
+			("symmetric", None) => Ok(WallPolicy::Symmetric),
+			("density", Some(arg)) => match arg.parse::<f64>() {
+				Ok(density) if (0.0..=1.0).contains(&density) => {
+					Ok(WallPolicy::Density { density })
+				}
+				_ => Err(format!("invalid density {:?}", arg)),
+			},
+			("edge-stubs", Some(arg)) => match arg.parse() {
+				Ok(stubs) => Ok(WallPolicy::EdgeStubs { stubs }),
+				_ => Err(format!("invalid number of stubs {:?}", arg)),
+			},
+			_ => Err(format!("unknown wall policy {:?}", s)),
+		}
+	}
+}
+
+impl WallPolicy {
+	pub(crate) fn place_walls(&self, bo: &mut Board, rng: &mut BoardRng, wall_num: usize) {
+		match *self {
+			WallPolicy::RandomPairs => {
+				for _ in 0..wall_num {
+					let mut add_walls = vec![];
+					let cy = rng.gen_range(0, bo.h);
+					let cx = rng.gen_range(0, bo.w);
+					let y = cy + rng.gen_range(0, 2);
+					if 0 < y && y < bo.h {
+						add_walls.push(wall(y, cx, 0));
+					}
+					let x = cx + rng.gen_range(0, 2);
+					if 0 < x && x < bo.w {
+						add_walls.push(wall(cy, x, 1));
+					}
+					bo.try_add_walls(add_walls);
+				}
+			}
+			WallPolicy::LShaped => place_corners(bo, rng, wall_num),
+			WallPolicy::Symmetric => {
+				for _ in 0..wall_num {
+					let cy = rng.gen_range(0, bo.h);
+					let cx = rng.gen_range(0, bo.w);
+					let y = cy + rng.gen_range(0, 2);
+					let x = cx + rng.gen_range(0, 2);
+					let mut add_walls = vec![];
+					for &w in &[wall(y, cx, 0), wall(cy, x, 1)] {
+						for &w in &[w, rotate(bo, &w)] {
+							if is_inner(bo, &w) && !add_walls.iter().any(|v| same_wall(v, &w)) {
+								add_walls.push(w);
+							}
+						}
+					}
+					bo.try_add_walls(add_walls);
+				}
+			}
+			WallPolicy::Density { density } => {
+				let inner_edges = (bo.h - 1) * bo.w + bo.h * (bo.w - 1);
+				let target = (density * inner_edges as f64).round() as usize;
+				let mut count = 0;
+				for _ in 0..(4 * inner_edges) {
+					if count >= target {
+						break;
+					}
+					// A single row or column only has walls in the other direction.
+					let horizontal = if bo.h < 2 {
+						false
+					} else if bo.w < 2 {
+						true
+					} else {
+						rng.gen_range(0, 2) == 0
+					};
+					let w = if horizontal {
+						wall(rng.gen_range(1, bo.h), rng.gen_range(0, bo.w), 0)
+					} else {
+						wall(rng.gen_range(0, bo.h), rng.gen_range(1, bo.w), 1)
+					};
+					if !is_inner(bo, &w) || bo.walls.iter().any(|v| same_wall(v, &w)) {
+						continue;
+					}
+					if bo.try_add_walls(vec![w]) {
+						count += 1;
+					}
+				}
+			}
+			WallPolicy::EdgeStubs { stubs } => {
+				// Stubs on the same side mustn't be next to each other.
+				let sides: [(usize, usize); 4] = [(0, 1), (bo.h - 1, 1), (0, 0), (bo.w - 1, 0)];
+				for &(fixed, d) in &sides {
+					let len = if d == 1 { bo.w } else { bo.h };
+					if len < 2 {
+						continue;
+					}
+					let mut placed: Vec<usize> = vec![];
+					for _ in 0..(stubs * 10) {
+						if placed.len() >= stubs {
+							break;
+						}
+						let t = rng.gen_range(1, len);
+						if placed.iter().any(|&u| (u as isize - t as isize).abs() < 2) {
+							continue;
+						}
+						let w = if d == 1 {
+							wall(fixed, t, 1)
+						} else {
+							wall(t, fixed, 0)
+						};
+						if bo.try_add_walls(vec![w]) {
+							placed.push(t);
+						}
+					}
+				}
+				place_corners(bo, rng, wall_num);
+			}
+		}
+	}
+}
+
+/// Puts `num` L-shaped corners on inner cells so that no two corners touch each other.
+fn place_corners(bo: &mut Board, rng: &mut BoardRng, num: usize) {
+	if bo.h < 3 || bo.w < 3 {
+		return;
+	}
+	let mut corners: Vec<Pos> = vec![];
+	for _ in 0..(num * 10) {
+		if corners.len() >= num {
+			break;
+		}
+		let c = Pos {
+			y: rng.gen_range(1, bo.h - 1) as i8,
+			x: rng.gen_range(1, bo.w - 1) as i8,
+		};
+		let dy = rng.gen_range(0, 2);
+		let dx = rng.gen_range(0, 2);
+		if corners
+			.iter()
+			.any(|p| (p.y - c.y).abs() <= 1 && (p.x - c.x).abs() <= 1)
+		{
+			continue;
+		}
+		let (y, x) = (c.y as usize, c.x as usize);
+		if bo.try_add_walls(vec![wall(y + dy, x, 0), wall(y, x + dx, 1)]) {
+			corners.push(c);
+		}
+	}
+}
+
+fn wall(y: usize, x: usize, d: i8) -> WallPos {
+	WallPos {
+		y: y as i8,
+		x: x as i8,
+		d,
+	}
+}
+
+pub(crate) fn is_inner(bo: &Board, w: &WallPos) -> bool {
+	if w.d == 0 {
+		0 < w.y && (w.y as usize) < bo.h
+	} else {
+		0 < w.x && (w.x as usize) < bo.w
+	}
+}
+
+pub(crate) fn same_wall(a: &WallPos, b: &WallPos) -> bool {
+	a.y == b.y && a.x == b.x && a.d == b.d
+}
+
+/// The wall at the 180-degree rotated position.
+fn rotate(bo: &Board, w: &WallPos) -> WallPos {
+	let (h, wd) = (bo.h as i8, bo.w as i8);
+	if w.d == 0 {
+		WallPos {
+			y: h - w.y,
+			x: wd - 1 - w.x,
+			d: 0,
+		}
+	} else {
+		WallPos {
+			y: h - 1 - w.y,
+			x: wd - w.x,
+			d: 1,
+		}
+	}
+}
@@ -0,0 +1,385 @@
+use std::collections::HashSet;
+
+extern crate serde_derive;
+use serde_derive::{Deserialize, Serialize};
+
+use crate::difficulty::{DifficultyReport, DifficultyWeights};
+use crate::goal::select_goal;
+use crate::preset::Preset;
+use crate::{
+	randomize_colours, Board, BoardRng, Move, Pos, WallPos, GENERATION_TRIES, ROBOTS_COUNT,
+};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BoardRecord {
+	pub h: usize,
+	pub w: usize,
+	pub walls: Vec<WallPos>,
+	pub robots: Vec<Pos>,
+	#[serde(default)]
+	pub blocked: Vec<Pos>,
+	#[serde(default)]
+	pub holes: Vec<Pos>,
+	#[serde(default)]
+	pub torus: bool,
+	#[serde(default)]
+	pub teleporters: Vec<[Pos; 2]>,
+	#[serde(default)]
+	pub sticky: Vec<Pos>,
+	#[serde(default)]
+	pub ice: Vec<Pos>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GoalRecord {
+	pub colour: usize,
+	pub y: i8,
+	pub x: i8,
+}
+
+/// One line of the puzzle pool.
+#[derive(Serialize, Debug)]
+pub struct PuzzleRecord {
+	pub preset: String,
+	pub board: BoardRecord,
+	pub goal: GoalRecord,
+	pub answer: Vec<Move>,
+	pub optimal_length: usize,
+	pub solution_count: u64,
+	pub robots_used: usize,
+	pub difficulty: DifficultyReport,
+	pub hash: String,
+}
+
+impl BoardRecord {
+	pub fn new(bo: &Board) -> BoardRecord {
+		BoardRecord {
+			h: bo.h,
+			w: bo.w,
+			walls: bo.walls.clone(),
+			robots: bo.robots.to_vec(),
+			blocked: bo.blocked.clone(),
+			holes: bo.holes.clone(),
+			torus: bo.torus,
+			teleporters: bo.teleporters.clone(),
+			sticky: bo.sticky.clone(),
+			ice: bo.ice.clone(),
+		}
+	}
+
+	/// Rebuilds the board, checking that everything is on it and the robots are on distinct open cells.
+	pub fn to_board(&self) -> Result<Board, String> {
+		// `Prev` packs the coordinates into 6 bits, and 63 is taken by removed robots.
+		if self.h == 0 || self.w == 0 || self.h >= 63 || self.w >= 63 {
+			return Err(format!("invalid board size {}x{}", self.h, self.w));
+		}
+		let (h, w) = (self.h as i8, self.w as i8);
+		let on_board = |p: &Pos| 0 <= p.y && p.y < h && 0 <= p.x && p.x < w;
+		let teleporters = self.teleporters.iter().flat_map(|pads| pads.iter());
+		let mut cells = self
+			.robots
+			.iter()
+			.chain(&self.blocked)
+			.chain(&self.holes)
+			.chain(&self.sticky)
+			.chain(&self.ice)
+			.chain(teleporters);
+		if let Some(p) = cells.find(|p| !on_board(p)) {
+			return Err(format!("{:?} is out of the board", p));
+		}
+		if let Some(wall) = self.walls.iter().find(|wall| {
+			!(0..=h).contains(&wall.y) || !(0..=w).contains(&wall.x) || !(0..=1).contains(&wall.d)
+		}) {
+			return Err(format!("invalid wall {:?}", wall));
+		}
+		if self.robots.len() != ROBOTS_COUNT {
+			return Err(format!(
+				"expected {} robots, got {}",
+				ROBOTS_COUNT,
+				self.robots.len()
+			));
+		}
+
+		let mut bo = Board {
+			w: self.w,
+			h: self.h,
+			walls: self.walls.clone(),
+			walldist: vec![],
+			robots: [Pos { y: 0, x: 0 }; ROBOTS_COUNT],
+			blocked: self.blocked.clone(),
+			holes: self.holes.clone(),
+			torus: self.torus,
+			teleporters: self.teleporters.clone(),
+			sticky: self.sticky.clone(),
+			ice: self.ice.clone(),
+		};
+		bo.robots.copy_from_slice(&self.robots);
+		bo.rebuild_walldist();
+
+		for (i, &p) in bo.robots.iter().enumerate() {
+			if bo.is_blocked(p.y as usize, p.x as usize)
+				|| bo.is_hole(p)
+				|| bo.robots[..i].contains(&p)
+			{
+				return Err(format!("robot {} can't stand on {:?}", i, p));
+			}
+		}
+		Ok(bo)
+	}
+}
+
+/**
+ * Generates the hardest puzzle within the depth of the preset on a random board of it,
+ * with the goal picked by the options of the preset.
+ * Returns `None` when the robots don't fit, when the board has no move at all,
+ * or when the options of the preset don't accept the puzzle (see `BoardOptions::accepts`).
+ */
+pub fn generate_puzzle<R: rand::Rng>(
+	preset: &Preset,
+	board_rng: BoardRng,
+	rng: &mut R,
+) -> Option<PuzzleRecord> {
+	let mut bo = Board::with_options(
+		preset.h,
+		preset.w,
+		board_rng,
+		preset.wall_num,
+		&preset.options,
+	)
+	.ok()?;
+	let (strategy, (goalcolour, goalpos), log) =
+		select_goal(preset.depth, &bo, preset.options.goal, rng);
+	if log.is_empty() {
+		return None;
+	}
+	if !preset
+		.options
+		.accepts(&bo, (goalcolour, goalpos), log.len())
+	{
+		return None;
+	}
+	let difficulty = DifficultyReport::new(
+		&bo,
+		(goalcolour, goalpos),
+		log.len(),
+		&DifficultyWeights::default(),
+	);
+
+	let log = log.into_iter().rev().collect();
+	let (goalcolour, log): (usize, Vec<Move>) = randomize_colours(&mut bo, goalcolour, log, rng);
+	let (goalcolour, log) = strategy.keep_colour(&mut bo, goalcolour, log);
+
+	let mut robots_used: Vec<usize> = log.iter().map(|m| m.c).collect();
+	robots_used.sort_unstable();
+	robots_used.dedup();
+
+	Some(PuzzleRecord {
+		preset: preset.name.clone(),
+		goal: GoalRecord {
+			colour: goalcolour,
+			y: goalpos.y,
+			x: goalpos.x,
+		},
+		optimal_length: log.len(),
+		solution_count: difficulty.solution_count,
+		robots_used: robots_used.len(),
+		difficulty,
+		hash: format!("{:016x}", canonical_hash(&bo, (goalcolour, goalpos))),
+		board: BoardRecord::new(&bo),
+		answer: log,
+	})
+}
+
+/**
+ * Generates `count` distinct puzzles of the preset.
+ * Puzzles whose canonical hash is already in `seen` are skipped.
+ * Fails after `GENERATION_TRIES` boards per puzzle, when the preset rejects most boards
+ * or has fewer distinct puzzles than `count`.
+ */
+pub fn generate_pool<R: rand::Rng>(
+	preset: &Preset,
+	count: usize,
+	seen: &mut HashSet<String>,
+	rng: &mut R,
+) -> Result<Vec<PuzzleRecord>, String> {
+	let mut res = Vec::with_capacity(count);
+	let max_tries = count * GENERATION_TRIES;
+	let mut tries = 0;
+	while res.len() < count {
+		if tries >= max_tries {
+			return Err(format!(
+				"only {} of {} distinct puzzles of {} in {} boards",
+				res.len(),
+				count,
+				preset.name,
+				tries
+			));
+		}
+		tries += 1;
+		let states: [u64; 2] = [rng.gen(), rng.gen()];
+		let board_rng: BoardRng = xorshift::SeedableRng::from_seed(&states[..]);
+		if let Some(puzzle) = generate_puzzle(preset, board_rng, rng) {
+			if seen.insert(puzzle.hash.clone()) {
+				res.push(puzzle);
+			}
+		}
+	}
+	Ok(res)
+}
+
+/**
+ * Hashes the puzzle so that boards equal up to flipping (and transposing, for square boards)
+ * and up to the colours of the robots other than the goal robot get the same value.
+ */
+pub fn canonical_hash(bo: &Board, goal: (usize, Pos)) -> u64 {
+	let (h, w) = (bo.h as i8, bo.w as i8);
+	let mut transforms: Vec<Box<dyn Fn(Pos) -> Pos>> = vec![
+		Box::new(|p: Pos| p),
+		Box::new(move |p: Pos| Pos {
+			y: h - 1 - p.y,
+			x: p.x,
+		}),
+		Box::new(move |p: Pos| Pos {
+			y: p.y,
+			x: w - 1 - p.x,
+		}),
+		Box::new(move |p: Pos| Pos {
+			y: h - 1 - p.y,
+			x: w - 1 - p.x,
+		}),
+	];
+	if h == w {
+		transforms.push(Box::new(|p: Pos| Pos { y: p.x, x: p.y }));
+		transforms.push(Box::new(move |p: Pos| Pos {
+			y: w - 1 - p.x,
+			x: p.y,
+		}));
+		transforms.push(Box::new(move |p: Pos| Pos {
+			y: p.x,
+			x: h - 1 - p.y,
+		}));
+		transforms.push(Box::new(move |p: Pos| Pos {
+			y: w - 1 - p.x,
+			x: h - 1 - p.y,
+		}));
+	}
+
+	let key = transforms
+		.iter()
+		.map(|f| canonical_bytes(bo, goal, f))
+		.min()
+		.unwrap();
+	fnv1a(&key)
+}
+
+fn canonical_bytes(bo: &Board, goal: (usize, Pos), f: &dyn Fn(Pos) -> Pos) -> Vec<u8> {
+	let corner = f(Pos {
+		y: bo.h as i8 - 1,
+		x: bo.w as i8 - 1,
+	});
+	let origin = f(Pos { y: 0, x: 0 });
+	let mut res = vec![
+		((corner.y - origin.y).abs() + 1) as u8,
+		((corner.x - origin.x).abs() + 1) as u8,
+		bo.torus as u8,
+	];
+	let (th, tw) = (res[0] as i8, res[1] as i8);
+
+	// A wall separates two adjacent cells, which are mapped independently.
+	let mut walls: Vec<(i8, i8, i8)> = bo
+		.walls
+		.iter()
+		.map(|wall| {
+			let a = f(Pos {
+				y: wall.y,
+				x: wall.x,
+			});
+			let b = if wall.d == 0 {
+				f(Pos {
+					y: wall.y - 1,
+					x: wall.x,
+				})
+			} else {
+				f(Pos {
+					y: wall.y,
+					x: wall.x - 1,
+				})
+			};
+			// Walls on the wrapping edge of a torus board are put on the first row (or column).
+			if a.x == b.x {
+				(a.y.max(b.y).rem_euclid(th), a.x, 0)
+			} else {
+				(a.y, a.x.max(b.x).rem_euclid(tw), 1)
+			}
+		})
+		.collect();
+	walls.sort_unstable();
+	walls.dedup();
+	res.push(walls.len() as u8);
+	for (y, x, d) in walls {
+		res.extend_from_slice(&[y as u8, x as u8, d as u8]);
+	}
+
+	for cells in &[&bo.blocked, &bo.holes, &bo.sticky, &bo.ice] {
+		let mut cells: Vec<(u8, u8)> = cells
+			.iter()
+			.map(|&p| {
+				let p = f(p);
+				(p.y as u8, p.x as u8)
+			})
+			.collect();
+		cells.sort_unstable();
+		res.push(cells.len() as u8);
+		for (y, x) in cells {
+			res.extend_from_slice(&[y, x]);
+		}
+	}
+
+	let mut teleporters: Vec<Vec<u8>> = bo
+		.teleporters
+		.iter()
+		.map(|pads| {
+			let mut pads: Vec<(u8, u8)> = pads
+				.iter()
+				.map(|&p| {
+					let p = f(p);
+					(p.y as u8, p.x as u8)
+				})
+				.collect();
+			pads.sort_unstable();
+			vec![pads[0].0, pads[0].1, pads[1].0, pads[1].1]
+		})
+		.collect();
+	teleporters.sort_unstable();
+	res.push(teleporters.len() as u8);
+	for pads in teleporters {
+		res.extend_from_slice(&pads);
+	}
+
+	let goalpos = f(goal.1);
+	res.extend_from_slice(&[goalpos.y as u8, goalpos.x as u8]);
+	let goalrobot = f(bo.robots[goal.0]);
+	res.extend_from_slice(&[goalrobot.y as u8, goalrobot.x as u8]);
+
+	let mut others: Vec<(u8, u8)> = (0..bo.robots.len())
+		.filter(|&i| i != goal.0)
+		.map(|i| {
+			let p = f(bo.robots[i]);
+			(p.y as u8, p.x as u8)
+		})
+		.collect();
+	others.sort_unstable();
+	for (y, x) in others {
+		res.extend_from_slice(&[y, x]);
+	}
+	res
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+	let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+	for &b in bytes {
+		hash ^= u64::from(b);
+		hash = hash.wrapping_mul(0x0100_0000_01b3);
+	}
+	hash
+}
@@ -0,0 +1,62 @@
+use std::collections::HashSet;
+use std::env;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+extern crate getopts;
+use getopts::Options;
+
+extern crate ricochet_robots;
+use ricochet_robots::pool::generate_pool;
+use ricochet_robots::preset::{default_presets, find_preset, load_presets, Preset};
+
+extern crate serde_json;
+
+fn main() -> Result<(), Box<dyn std::error::Error + 'static>> {
+	let args: Vec<String> = env::args().collect();
+	let mut opts = Options::new();
+	opts.optopt(
+		"",
+		"presets",
+		"the presets file (defaults to the bundled presets.json)",
+		"PATH",
+	);
+	let matches = opts.parse(&args[1..])?;
+	let args = &matches.free;
+	if args.len() < 2 {
+		return Err(format!(
+			"invalid argument. expect \"[--presets PATH] count output_path [preset...]\", got {:?}.",
+			args
+		)
+		.into());
+	}
+	let count: usize = args[0].parse()?;
+	let all_presets = match matches.opt_str("presets") {
+		Some(path) => load_presets(path)?,
+		None => default_presets(),
+	};
+	let presets: Vec<Preset> = if args.len() > 2 {
+		args[2..]
+			.iter()
+			.map(|name| {
+				find_preset(&all_presets, name)
+					.cloned()
+					.ok_or_else(|| format!("unknown preset {:?}", name))
+			})
+			.collect::<Result<_, _>>()?
+	} else {
+		all_presets
+	};
+
+	let mut out = BufWriter::new(File::create(&args[1])?);
+	let mut rng = rand::thread_rng();
+	let mut seen = HashSet::new();
+	for preset in &presets {
+		for puzzle in generate_pool(preset, count, &mut seen, &mut rng)? {
+			writeln!(out, "{}", serde_json::to_string(&puzzle)?)?;
+		}
+		eprintln!("generated {} puzzles of {}", count, preset.name);
+	}
+	out.flush()?;
+	Ok(())
+}
@@ -0,0 +1,165 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+extern crate serde_derive;
+use serde_derive::Deserialize;
+
+use crate::{BoardOptions, ROBOTS_COUNT};
+
+/// A named kind of board the bot offers (ベイビー / スーパー / ハイパー).
+#[derive(Debug, Clone)]
+pub struct Preset {
+	pub name: String,
+	/// Other names of the preset, such as the ones players type.
+	pub aliases: Vec<String>,
+	pub h: usize,
+	pub w: usize,
+	pub wall_num: usize,
+	/// The depth given to `bfs`. The shortest accepted answer is `options.min_length`.
+	pub depth: u8,
+	pub options: BoardOptions,
+}
+
+/// The presets in `presets.json`, used unless another file is given.
+const BUNDLED_PRESETS: &str = include_str!("../presets.json");
+
+/// A preset as written in the file. Everything but the board size can be left out.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PresetConfig {
+	name: String,
+	#[serde(default)]
+	aliases: Vec<String>,
+	height: usize,
+	width: usize,
+	walls: usize,
+	#[serde(default = "default_robots")]
+	robots: usize,
+	#[serde(default)]
+	depth: DepthRange,
+	policy: Option<String>,
+	shape: Option<String>,
+	#[serde(default)]
+	holes: usize,
+	#[serde(default)]
+	torus: bool,
+	#[serde(default)]
+	teleporters: usize,
+	#[serde(default)]
+	sticky: usize,
+	#[serde(default)]
+	ice: usize,
+	#[serde(default)]
+	min_robots: usize,
+	#[serde(default)]
+	require_blocker: bool,
+	goal: Option<String>,
+}
+
+fn default_robots() -> usize {
+	ROBOTS_COUNT
+}
+
+/// The lengths of the answers the preset accepts.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DepthRange {
+	min: usize,
+	max: u8,
+}
+
+impl Default for DepthRange {
+	fn default() -> Self {
+		DepthRange {
+			min: 0,
+			max: u8::MAX,
+		}
+	}
+}
+
+impl PresetConfig {
+	fn into_preset(self) -> Result<Preset, String> {
+		let fail = |e: String| format!("preset {:?}: {}", self.name, e);
+		// The states pack exactly `ROBOTS_COUNT` robots.
+		if self.robots != ROBOTS_COUNT {
+			return Err(fail(format!(
+				"only {} robots are supported, got {}",
+				ROBOTS_COUNT, self.robots
+			)));
+		}
+		if self.depth.min > self.depth.max as usize {
+			return Err(fail(format!(
+				"the depth range {}..={} is empty",
+				self.depth.min, self.depth.max
+			)));
+		}
+		let mut options = BoardOptions::default();
+		if let Some(policy) = &self.policy {
+			options.policy = policy.parse().map_err(fail)?;
+		}
+		if let Some(shape) = &self.shape {
+			options.shape = shape.parse().map_err(fail)?;
+		}
+		if let Some(goal) = &self.goal {
+			options.goal = goal.parse().map_err(fail)?;
+		}
+		options.hole_num = self.holes;
+		options.torus = self.torus;
+		options.teleporter_num = self.teleporters;
+		options.sticky_num = self.sticky;
+		options.ice_num = self.ice;
+		options.min_robots = self.min_robots;
+		options.require_blocker = self.require_blocker;
+		options.min_length = self.depth.min;
+		options
+			.check(self.height, self.width, self.walls)
+			.map_err(fail)?;
+		Ok(Preset {
+			name: self.name,
+			aliases: self.aliases,
+			h: self.height,
+			w: self.width,
+			wall_num: self.walls,
+			depth: self.depth.max,
+			options,
+		})
+	}
+}
+
+/// Parses a JSON array of presets. The names and aliases must be unique.
+pub fn parse_presets(json: &str) -> Result<Vec<Preset>, String> {
+	let configs: Vec<PresetConfig> =
+		serde_json::from_str(json).map_err(|e| format!("invalid presets: {}", e))?;
+	let presets = configs
+		.into_iter()
+		.map(PresetConfig::into_preset)
+		.collect::<Result<Vec<_>, _>>()?;
+	let mut names = HashSet::new();
+	for preset in &presets {
+		for name in std::iter::once(&preset.name).chain(&preset.aliases) {
+			if !names.insert(name) {
+				return Err(format!("the preset name {:?} is used twice", name));
+			}
+		}
+	}
+	Ok(presets)
+}
+
+pub fn load_presets<P: AsRef<Path>>(path: P) -> Result<Vec<Preset>, String> {
+	let path = path.as_ref();
+	let json =
+		fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+	parse_presets(&json)
+}
+
+pub fn default_presets() -> Vec<Preset> {
+	parse_presets(BUNDLED_PRESETS).expect("presets.json is invalid")
+}
+
+/// Finds the preset by its name or one of its aliases.
+pub fn find_preset<'a>(presets: &'a [Preset], name: &str) -> Option<&'a Preset> {
+	presets
+		.iter()
+		.find(|p| p.name == name || p.aliases.iter().any(|a| a == name))
+}
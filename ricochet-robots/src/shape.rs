@@ -0,0 +1,54 @@
+use std::str::FromStr;
+
+use crate::Pos;
+
+/// The outline of the board, given by the cells blocked from the start.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shape {
+	Rectangle,
+	/// Blocks the central cell (or 2x2 cells on boards of even size) like the physical game.
+	CentralBlock,
+	/// Blocks the four corners, a third of the height and width each.
+	Cross,
+	/// Blocks the middle third of the board.
+	Donut,
+}
+
+impl FromStr for Shape {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"rectangle" => Ok(Shape::Rectangle),
+			"central-block" => Ok(Shape::CentralBlock),
+			"cross" => Ok(Shape::Cross),
+			"donut" => Ok(Shape::Donut),
+			_ => Err(format!("unknown shape {:?}", s)),
+		}
+	}
+}
+
+impl Shape {
+	pub fn blocked_cells(&self, h: usize, w: usize) -> Vec<Pos> {
+		let (ch, cw) = (h / 3, w / 3);
+		let is_blocked = |y: usize, x: usize| match *self {
+			Shape::Rectangle => false,
+			Shape::CentralBlock => (h - 1) / 2 <= y && y <= h / 2 && (w - 1) / 2 <= x && x <= w / 2,
+			Shape::Cross => (y < ch || h - ch <= y) && (x < cw || w - cw <= x),
+			Shape::Donut => ch <= y && y < h - ch && cw <= x && x < w - cw,
+		};
+
+		let mut res = vec![];
+		for y in 0..h {
+			for x in 0..w {
+				if is_blocked(y, x) {
+					res.push(Pos {
+						y: y as i8,
+						x: x as i8,
+					});
+				}
+			}
+		}
+		res
+	}
+}
@@ -0,0 +1,918 @@
+//! Property tests of the movement rules and the solver against naive reimplementations,
+//! and tests of the tools built on them against hand-worked boards.
+
+use std::collections::HashSet;
+
+use proptest::prelude::*;
+use rand::SeedableRng;
+
+use super::*;
+use crate::cost::{self, CostModel};
+use crate::policy::is_inner;
+
+fn board_options() -> impl Strategy<Value = BoardOptions> {
+	(any::<bool>(), 0..2usize, 0..2usize, 0..3usize).prop_map(
+		|(torus, hole_num, teleporter_num, sticky_num)| BoardOptions {
+			torus,
+			hole_num,
+			teleporter_num,
+			sticky_num,
+			..BoardOptions::default()
+		},
+	)
+}
+
+prop_compose! {
+	/// Small boards with every kind of cell, so that the naive solver stays fast.
+	fn small_board()(
+		seed in any::<u64>(),
+		h in 3..6usize,
+		w in 3..6usize,
+		wall_num in 0..8usize,
+		options in board_options(),
+	) -> Board {
+		let rng: BoardRng = xorshift::SeedableRng::from_seed(&[seed, seed ^ 0x5555][..]);
+		Board::with_options(h, w, rng, wall_num, &options).expect("the options fit on 3x3")
+	}
+}
+
+/// Whether a wall (or the border of a board which isn't a torus) is on the way from `p` to `d`.
+fn wall_ahead(bo: &Board, p: Pos, d: usize) -> bool {
+	let (h, w) = (bo.h as i8, bo.w as i8);
+	let has_wall = |y: i8, x: i8, wd: i8| {
+		let walls = bo.blocked.iter().flat_map(|b| {
+			vec![
+				(b.y, b.x, 0),
+				(b.y + 1, b.x, 0),
+				(b.y, b.x, 1),
+				(b.y, b.x + 1, 1),
+			]
+		});
+		let mut walls = bo.walls.iter().map(|w| (w.y, w.x, w.d)).chain(walls);
+		walls.any(|wall| wall == (y, x, wd))
+	};
+	// The edge crossed by the move, as the row (or column) below (or right of) it.
+	let (edge, along, wd, len) = match d {
+		0 => (p.y + 1, p.x, 0, h),
+		1 => (p.x + 1, p.y, 1, w),
+		2 => (p.y, p.x, 0, h),
+		_ => (p.x, p.y, 1, w),
+	};
+	let wall_at = |e: i8| {
+		if wd == 0 {
+			has_wall(e, along, 0)
+		} else {
+			has_wall(along, e, 1)
+		}
+	};
+	if edge == 0 || edge == len {
+		!bo.torus || wall_at(0) || wall_at(len)
+	} else {
+		wall_at(edge)
+	}
+}
+
+fn step(bo: &Board, p: Pos, d: usize) -> Pos {
+	Pos {
+		y: (p.y + DIRECTIONS[d].y).rem_euclid(bo.h as i8),
+		x: (p.x + DIRECTIONS[d].x).rem_euclid(bo.w as i8),
+	}
+}
+
+/// Slides the robot cell by cell, following the rules literally.
+fn naive_move(
+	bo: &Board,
+	robots: &[Pos; ROBOTS_COUNT],
+	c: usize,
+	d: usize,
+) -> Option<[Pos; ROBOTS_COUNT]> {
+	let start = robots[c];
+	if start == REMOVED {
+		return None;
+	}
+	let occupied = |p: Pos| (0..ROBOTS_COUNT).any(|j| j != c && robots[j] == p);
+	let mut seen = HashSet::new();
+	seen.insert((start.y, start.x));
+	let mut p = start;
+	while !wall_ahead(bo, p, d) {
+		let q = step(bo, p, d);
+		if occupied(q) {
+			break;
+		}
+		p = q;
+		if bo.sticky.contains(&p) {
+			break;
+		}
+		if let Some(partner) = bo.teleporter_partner(p) {
+			if !occupied(partner) {
+				p = partner;
+			}
+		}
+		if !seen.insert((p.y, p.x)) {
+			return None;
+		}
+	}
+	if p == start {
+		return None;
+	}
+	let mut res = *robots;
+	res[c] = if bo.holes.contains(&p) { REMOVED } else { p };
+	Some(res)
+}
+
+/// Whether some sequence of at most `depth` moves brings the robot `goal.0` onto `goal.1`.
+fn naive_reachable(
+	bo: &Board,
+	robots: &[Pos; ROBOTS_COUNT],
+	goal: (usize, Pos),
+	depth: usize,
+) -> bool {
+	if robots[goal.0] == goal.1 {
+		return true;
+	}
+	depth > 0
+		&& (0..ROBOTS_COUNT).any(|c| {
+			(0..4).any(|d| match naive_move(bo, robots, c, d) {
+				Some(next) => naive_reachable(bo, &next, goal, depth - 1),
+				None => false,
+			})
+		})
+}
+
+/// Whether some sequence of exactly `length` moves ends with the robot `goal.0` on `goal.1`.
+fn naive_exact(
+	bo: &Board,
+	robots: &[Pos; ROBOTS_COUNT],
+	goal: (usize, Pos),
+	length: usize,
+) -> bool {
+	if length == 0 {
+		return robots[goal.0] == goal.1;
+	}
+	(0..ROBOTS_COUNT).any(|c| {
+		(0..4).any(|d| match naive_move(bo, robots, c, d) {
+			Some(next) => naive_exact(bo, &next, goal, length - 1),
+			None => false,
+		})
+	})
+}
+
+/// Distinct cells which aren't blocked, for placing robots anywhere.
+fn open_positions(bo: &Board, picks: &[(usize, usize)]) -> Option<[Pos; ROBOTS_COUNT]> {
+	let mut res = [REMOVED; ROBOTS_COUNT];
+	for (i, &(y, x)) in picks.iter().enumerate() {
+		let p = Pos {
+			y: (y % bo.h) as i8,
+			x: (x % bo.w) as i8,
+		};
+		if bo.is_blocked(p.y as usize, p.x as usize) || res[..i].contains(&p) {
+			return None;
+		}
+		res[i] = p;
+	}
+	Some(res)
+}
+
+/// Reads a board written like `BoardRecord`, with the features left out defaulting to none.
+fn parse_board(json: &str) -> Board {
+	let record: pool::BoardRecord = serde_json::from_str(json).expect("invalid board record");
+	record.to_board().expect("invalid board")
+}
+
+/// A board without features or walls, with the robots in the four corners of 3x3.
+const CORNERS: &str = r#"{"h":3,"w":3,"walls":[],"robots":[{"y":0,"x":0},{"y":0,"x":2},{"y":2,"x":0},{"y":2,"x":2}]}"#;
+
+fn corners_board() -> Board {
+	parse_board(CORNERS)
+}
+
+/**
+ * The robots on the four left cells of a 1x5 row. Only the free cell moves,
+ * so the only way to `ROW_GOAL` is moving the robots right one by one from the rightmost.
+ */
+fn row_board() -> Board {
+	parse_board(
+		r#"{"h":1,"w":5,"walls":[],"robots":[{"y":0,"x":0},{"y":0,"x":1},{"y":0,"x":2},{"y":0,"x":3}]}"#,
+	)
+}
+
+/// Robot 0 one cell to the right, 4 moves away on `row_board`.
+const ROW_GOAL: (usize, Pos) = (0, Pos { y: 0, x: 1 });
+
+/// Maps the board and the goal through `f`, which takes the board onto a board of `h` x `w`,
+/// and gives robot `c` the colour `perm[c]`.
+fn map_board(
+	bo: &Board,
+	goal: (usize, Pos),
+	h: usize,
+	w: usize,
+	f: &dyn Fn(Pos) -> Pos,
+	perm: &[usize],
+) -> (Board, (usize, Pos)) {
+	let mut record = pool::BoardRecord::new(bo);
+	let cells = |cells: &[Pos]| cells.iter().map(|&p| f(p)).collect::<Vec<_>>();
+	record.h = h;
+	record.w = w;
+	record.walls = bo
+		.walls
+		.iter()
+		.map(|wall| {
+			let a = f(Pos {
+				y: wall.y,
+				x: wall.x,
+			});
+			let b = if wall.d == 0 {
+				f(Pos {
+					y: wall.y - 1,
+					x: wall.x,
+				})
+			} else {
+				f(Pos {
+					y: wall.y,
+					x: wall.x - 1,
+				})
+			};
+			if a.x == b.x {
+				WallPos {
+					y: a.y.max(b.y),
+					x: a.x,
+					d: 0,
+				}
+			} else {
+				WallPos {
+					y: a.y,
+					x: a.x.max(b.x),
+					d: 1,
+				}
+			}
+		})
+		.collect();
+	for (c, &p) in bo.robots.iter().enumerate() {
+		record.robots[perm[c]] = f(p);
+	}
+	record.blocked = cells(&bo.blocked);
+	record.holes = cells(&bo.holes);
+	record.sticky = cells(&bo.sticky);
+	record.ice = cells(&bo.ice);
+	record.teleporters = bo.teleporters.iter().map(|&[a, b]| [f(a), f(b)]).collect();
+	(
+		record.to_board().expect("the mapped board is valid"),
+		(perm[goal.0], f(goal.1)),
+	)
+}
+
+proptest! {
+	#[test]
+	fn prev_round_trips(c in 0..4usize, d in 0..4usize, y in 0..64i8, x in 0..64i8) {
+		let (m, p) = Prev::serialize(&Move { c, d }, &Pos { y, x }).deserialize();
+		prop_assert_eq!((m.c, m.d, p), (c, d, Pos { y, x }));
+	}
+
+	#[test]
+	fn command_round_trips(moves in proptest::collection::vec((0..4usize, 0..4usize), 1..12), made in any::<bool>()) {
+		let command = notation::Command {
+			moves: moves.iter().map(|&(c, d)| Move { c, d }).collect(),
+			made,
+		};
+		let parsed = notation::parse_command(&command.to_string()).unwrap();
+		let pairs: Vec<_> = parsed.moves.iter().map(|m| (m.c, m.d)).collect();
+		prop_assert_eq!((pairs, parsed.made), (moves, made));
+	}
+
+	#[test]
+	fn walldist_matches_ray_casting(bo in small_board()) {
+		let len = |d: usize| if DIRECTIONS[d].x == 0 { bo.h } else { bo.w };
+		for y in 0..bo.h {
+			for x in 0..bo.w {
+				for d in 0..4 {
+					let start = Pos { y: y as i8, x: x as i8 };
+					let mut p = start;
+					let mut dist = 0;
+					while !wall_ahead(&bo, p, d) && dist < len(d) {
+						p = step(&bo, p, d);
+						dist += 1;
+						if bo.sticky.contains(&p) {
+							break;
+						}
+					}
+					// Coming back to the start means there is no wall around the row (or column).
+					if p == start && dist > 0 {
+						dist = NO_WALL;
+					}
+					prop_assert_eq!(bo.walldist[y][x][d], dist, "at ({}, {}) to {}", y, x, d);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn move_to_matches_naive_move(
+		bo in small_board(),
+		picks in proptest::collection::vec((0..8usize, 0..8usize), ROBOTS_COUNT),
+		removed in 0..=ROBOTS_COUNT,
+	) {
+		let mut robots = match open_positions(&bo, &picks) {
+			Some(robots) => robots,
+			None => return Ok(()),
+		};
+		if removed < ROBOTS_COUNT {
+			robots[removed] = REMOVED;
+		}
+		let st = State { robots };
+		for c in 0..ROBOTS_COUNT {
+			for d in 0..4 {
+				let res = st.move_to(&bo, c, d).map(|ts| ts.robots);
+				prop_assert_eq!(res, naive_move(&bo, &robots, c, d), "robot {} to {}", c, d);
+			}
+		}
+	}
+
+	#[test]
+	fn checked_options_generate(
+		seed in any::<u64>(),
+		h in 1..5usize,
+		w in 1..5usize,
+		wall_num in 0..4usize,
+		(hole_num, teleporter_num, sticky_num, ice_num) in (0..3usize, 0..2usize, 0..4usize, 0..4usize),
+	) {
+		let options = BoardOptions {
+			hole_num,
+			teleporter_num,
+			sticky_num,
+			ice_num,
+			..BoardOptions::default()
+		};
+		if options.check(h, w, wall_num).is_ok() {
+			let rng: BoardRng = xorshift::SeedableRng::from_seed(&[seed, !seed][..]);
+			prop_assert!(Board::with_options(h, w, rng, wall_num, &options).is_ok());
+		}
+	}
+
+	#[test]
+	fn wall_policies_keep_boards_good(
+		seed in any::<u64>(),
+		h in 1..7usize,
+		w in 1..7usize,
+		wall_num in 0..8usize,
+		policy in prop_oneof![
+			Just(WallPolicy::RandomPairs),
+			Just(WallPolicy::LShaped),
+			Just(WallPolicy::Symmetric),
+			(0.0..=1.0f64).prop_map(|density| WallPolicy::Density { density }),
+			(0..3usize).prop_map(|stubs| WallPolicy::EdgeStubs { stubs }),
+		],
+	) {
+		let options = BoardOptions { policy, ..BoardOptions::default() };
+		prop_assume!(options.check(h, w, wall_num).is_ok());
+		let rng: BoardRng = xorshift::SeedableRng::from_seed(&[seed, !seed][..]);
+		let mut bo = Board::with_options(h, w, rng, wall_num, &options).expect("the robots fit");
+		for v in &bo.walls {
+			prop_assert!(is_inner(&bo, v), "{:?} is on the border", v);
+		}
+		// Walls are only kept when the board stays good, and boards like 1xN aren't good to begin with.
+		prop_assert!(bo.walls.is_empty() || bo.good_board());
+	}
+
+	#[test]
+	fn canonical_hash_ignores_symmetries(
+		bo in small_board(),
+		c in 0..ROBOTS_COUNT,
+		y in 0..5i8,
+		x in 0..5i8,
+		perm in Just((0..ROBOTS_COUNT).collect::<Vec<usize>>()).prop_shuffle(),
+	) {
+		let goal = (c, Pos { y: y % bo.h as i8, x: x % bo.w as i8 });
+		let (h, w) = (bo.h as i8, bo.w as i8);
+		let hash = pool::canonical_hash(&bo, goal);
+		let id = |p: Pos| p;
+		let flip_y = |p: Pos| Pos { y: h - 1 - p.y, x: p.x };
+		let flip_x = |p: Pos| Pos { y: p.y, x: w - 1 - p.x };
+		let transpose = |p: Pos| Pos { y: p.x, x: p.y };
+
+		let (mapped, mapped_goal) = map_board(&bo, goal, bo.h, bo.w, &id, &perm);
+		prop_assert_eq!(pool::canonical_hash(&mapped, mapped_goal), hash);
+		let (mapped, mapped_goal) = map_board(&bo, goal, bo.h, bo.w, &flip_y, &perm);
+		prop_assert_eq!(pool::canonical_hash(&mapped, mapped_goal), hash);
+		let (mapped, mapped_goal) = map_board(&bo, goal, bo.h, bo.w, &flip_x, &perm);
+		prop_assert_eq!(pool::canonical_hash(&mapped, mapped_goal), hash);
+		if bo.h == bo.w {
+			let (mapped, mapped_goal) = map_board(&bo, goal, bo.w, bo.h, &transpose, &perm);
+			prop_assert_eq!(pool::canonical_hash(&mapped, mapped_goal), hash);
+		}
+	}
+
+	#[test]
+	fn evaluate_answer_deviates_where_the_goal_gets_too_far(
+		bo in small_board(),
+		c in 0..ROBOTS_COUNT,
+		y in 0..5i8,
+		x in 0..5i8,
+		answer in prop::collection::vec((0..ROBOTS_COUNT, 0..4usize), 0..6),
+	) {
+		let goal = (c, Pos { y: y % bo.h as i8, x: x % bo.w as i8 });
+		let optimal_length = match solve(&bo, goal, 4) {
+			Some(moves) => moves.len(),
+			None => return Ok(()),
+		};
+		let moves: Vec<Move> = answer.iter().map(|&(c, d)| Move { c, d }).collect();
+		let res = evaluate::evaluate_answer(&bo, goal, optimal_length, &moves);
+
+		// A move deviates once the goal can't be reached in the moves left.
+		let mut st = State::init_state(&bo);
+		let mut first_deviation = None;
+		for (i, m) in moves.iter().enumerate() {
+			if let Some(ts) = st.move_to(&bo, m.c, m.d) {
+				st = ts;
+			}
+			let on_line = i < optimal_length
+				&& st.robots[goal.0] != REMOVED
+				&& solve_from(&bo, st.clone(), goal, optimal_length - i - 1).is_some();
+			if first_deviation.is_none() && !on_line {
+				first_deviation = Some(i);
+			}
+		}
+		prop_assert_eq!(res.first_deviation, first_deviation);
+		prop_assert_eq!(res.solved, st.robots[goal.0] == goal.1);
+	}
+
+	#[test]
+	fn bfs_path_replays_to_goal(bo in small_board(), target in 0..6u8) {
+		let (goal, log) = bfs(target, &bo);
+		let mut st = State::init_state(&bo);
+		for m in log.iter().rev() {
+			st = st.move_to(&bo, m.c, m.d).expect("the move is blocked");
+		}
+		prop_assert_eq!(st.robots[goal.0], goal.1);
+	}
+
+	#[test]
+	fn select_goal_keeps_bfs_depth(bo in small_board(), target in 0..6u8, c in 0..ROBOTS_COUNT, seed in any::<u64>()) {
+		let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+		let depth = bfs(target, &bo).1.len();
+		for &strategy in &[GoalStrategy::Uniform, GoalStrategy::Corner, GoalStrategy::Robot(c)] {
+			let (used, goal, log) = select_goal(target, &bo, strategy, &mut rng);
+			let mut st = State::init_state(&bo);
+			for m in log.iter().rev() {
+				st = st.move_to(&bo, m.c, m.d).expect("the move is blocked");
+			}
+			prop_assert_eq!(st.robots[goal.0], goal.1);
+			prop_assert!(log.len() <= depth);
+			prop_assert_eq!(solve(&bo, goal, depth).map(|moves| moves.len()), Some(log.len()));
+			match used {
+				GoalStrategy::Uniform => prop_assert_eq!(log.len(), depth),
+				GoalStrategy::Corner => prop_assert!(goal::is_corner(&bo, goal.1)),
+				GoalStrategy::Robot(robot) => prop_assert_eq!(goal.0, robot),
+				_ => {}
+			}
+			// Only `Corner` falls back, when no corner is reached.
+			prop_assert!(used == strategy || (strategy == GoalStrategy::Corner && used == GoalStrategy::Uniform));
+		}
+	}
+}
+
+proptest! {
+	// The naive solver tries every sequence, so keep the boards and depths small.
+	#![proptest_config(ProptestConfig::with_cases(64))]
+
+	#[test]
+	fn generated_problems_are_accepted(
+		seed in any::<u64>(),
+		min_length in 0..6usize,
+		min_robots in 0..3usize,
+		require_blocker in any::<bool>(),
+	) {
+		let options = BoardOptions {
+			min_length,
+			min_robots,
+			require_blocker,
+			..BoardOptions::default()
+		};
+		let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+		if let Ok(problem) = generate_problem(4, 4, 3, &options, 6, [seed, !seed], &mut rng) {
+			prop_assert!(options.accepts(&problem.board, problem.goal, problem.answer.len()));
+		}
+	}
+
+	#[test]
+	fn state_space_report_is_consistent(seed in any::<u64>(), h in 2..4usize, w in 2..4usize, wall_num in 0..4usize) {
+		prop_assume!(BoardOptions::default().check(h, w, wall_num).is_ok());
+		let rng: BoardRng = xorshift::SeedableRng::from_seed(&[seed, !seed][..]);
+		let bo = Board::new(h, w, rng, wall_num);
+		let report = analysis::StateSpaceReport::new(&bo);
+		prop_assert_eq!(report.depth_histogram.iter().sum::<usize>(), report.state_count);
+		prop_assert_eq!(report.depth_histogram.len(), report.eccentricity + 1);
+		for g in &report.farthest_goals {
+			let goal = (g.colour, Pos { y: g.y, x: g.x });
+			let answer = solve(&bo, goal, report.goal_depth);
+			prop_assert_eq!(answer.map(|moves| moves.len()), Some(report.goal_depth));
+		}
+	}
+
+	#[test]
+	fn optimise_layout_keeps_the_deepest_puzzle(
+		bo in small_board(),
+		seed in any::<u64>(),
+		iterations in 0..40usize,
+		temperature in prop_oneof![Just(0.0), Just(1.0)],
+		extra_walls in 0..3usize,
+	) {
+		let options = design::DesignOptions {
+			iterations,
+			target: 5,
+			temperature,
+			min_walls: 0,
+			max_walls: bo.walls.len() + extra_walls,
+		};
+		let initial = bfs(options.target, &bo).1.len();
+		let mut rng: BoardRng = xorshift::SeedableRng::from_seed(&[seed, !seed][..]);
+		let design = design::optimise_layout(bo, &options, &mut rng);
+		prop_assert!(design.evaluated <= iterations);
+		prop_assert!(design.board.walls.len() <= options.max_walls);
+		prop_assert!(design.answer.len() >= initial);
+
+		let mut robots = design.board.robots;
+		for m in &design.answer {
+			robots = naive_move(&design.board, &robots, m.c, m.d).expect("the move is blocked");
+		}
+		prop_assert_eq!(robots[design.goal.0], design.goal.1);
+		let optimal = solve(&design.board, design.goal, design.answer.len());
+		prop_assert_eq!(optimal.map(|moves| moves.len()), Some(design.answer.len()));
+	}
+
+	#[test]
+	fn bfs_runs_out_of_states_on_teleporter_boards(seed in any::<u64>(), torus in any::<bool>(), hole_num in 0..2usize) {
+		let options = BoardOptions {
+			torus,
+			hole_num,
+			teleporter_num: 1,
+			..BoardOptions::default()
+		};
+		let rng: BoardRng = xorshift::SeedableRng::from_seed(&[seed, !seed][..]);
+		let bo = Board::with_options(4, 4, rng, 2, &options).expect("the options fit on 4x4");
+		// Some robot/cell pairs can't be reached, so the search ends when the states do.
+		let (goal, log) = bfs(u8::MAX, &bo);
+		let mut st = State::init_state(&bo);
+		for m in log.iter().rev() {
+			st = st.move_to(&bo, m.c, m.d).expect("the move is blocked");
+		}
+		prop_assert_eq!(st.robots[goal.0], goal.1);
+	}
+
+	#[test]
+	fn solve_with_cost_beats_the_shortest_answer(bo in small_board(), c in 0..ROBOTS_COUNT, y in 0..5i8, x in 0..5i8) {
+		let goal = (c, Pos { y: y % bo.h as i8, x: x % bo.w as i8 });
+		let shortest = match solve(&bo, goal, 5) {
+			Some(moves) => moves,
+			None => return Ok(()),
+		};
+		let replay = |answer: &[Move]| {
+			let mut robots = bo.robots;
+			for m in answer {
+				robots = naive_move(&bo, &robots, m.c, m.d).expect("the move is blocked");
+			}
+			robots[goal.0] == goal.1
+		};
+		let max_cost = 3 * shortest.len() as u32;
+		let models = [
+			CostModel::Moves,
+			CostModel::RobotSelections,
+			CostModel::Weighted { goal: 0, other: 1 },
+			CostModel::Weighted { goal: 2, other: 3 },
+		];
+		for model in &models {
+			let report = cost::solve_with_cost(&bo, goal, model, max_cost).expect("the shortest answer is within the cost");
+			prop_assert!(replay(&report.answer));
+			let expected = cost::solve_with_cost(&bo, goal, model, report.cost).map(|r| r.cost);
+			prop_assert_eq!(expected, Some(report.cost));
+			// The cheapest answer is no dearer than the shortest one.
+			let shortest_cost = match *model {
+				CostModel::Moves => shortest.len() as u32,
+				CostModel::RobotSelections => 1 + shortest.windows(2).filter(|w| w[0].c != w[1].c).count() as u32,
+				CostModel::Weighted { goal: g, other } => shortest.iter().map(|m| if m.c == goal.0 { g } else { other }).sum(),
+			};
+			prop_assert!(report.cost <= shortest_cost);
+			if *model == CostModel::Moves {
+				prop_assert_eq!(report.cost, shortest.len() as u32);
+			}
+			if report.cost > 0 {
+				prop_assert!(cost::solve_with_cost(&bo, goal, model, report.cost - 1).is_none());
+			}
+		}
+	}
+
+	#[test]
+	fn bfs_path_is_shortest(bo in small_board(), target in 0..5u8) {
+		let (goal, log) = bfs(target, &bo);
+		if !log.is_empty() {
+			prop_assert!(!naive_reachable(&bo, &bo.robots, goal, log.len() - 1));
+		}
+	}
+
+	#[test]
+	fn solve_finds_shortest_paths(bo in small_board(), c in 0..ROBOTS_COUNT, y in 0..5i8, x in 0..5i8) {
+		let goal = (c, Pos { y: y % bo.h as i8, x: x % bo.w as i8 });
+		match solve(&bo, goal, 3) {
+			Some(moves) => {
+				let mut robots = bo.robots;
+				for m in &moves {
+					robots = naive_move(&bo, &robots, m.c, m.d).expect("the move is blocked");
+				}
+				prop_assert_eq!(robots[goal.0], goal.1);
+				prop_assert!(moves.is_empty() || !naive_reachable(&bo, &bo.robots, goal, moves.len() - 1));
+			}
+			None => prop_assert!(!naive_reachable(&bo, &bo.robots, goal, 3)),
+		}
+	}
+
+	#[test]
+	fn solve_exact_finds_exact_paths(bo in small_board(), c in 0..ROBOTS_COUNT, y in 0..5i8, x in 0..5i8, length in 0..4usize) {
+		let goal = (c, Pos { y: y % bo.h as i8, x: x % bo.w as i8 });
+		match solve_exact(&bo, goal, length) {
+			Some(moves) => {
+				prop_assert_eq!(moves.len(), length);
+				let mut robots = bo.robots;
+				for m in &moves {
+					robots = naive_move(&bo, &robots, m.c, m.d).expect("the move is blocked");
+				}
+				prop_assert_eq!(robots[goal.0], goal.1);
+			}
+			None => prop_assert!(!naive_exact(&bo, &bo.robots, goal, length)),
+		}
+	}
+
+	#[test]
+	fn solve_bidirectional_matches_solve(bo in small_board(), c in 0..ROBOTS_COUNT, y in 0..5i8, x in 0..5i8) {
+		let goal = (c, Pos { y: y % bo.h as i8, x: x % bo.w as i8 });
+		let expected = solve(&bo, goal, 6).map(|moves| moves.len());
+		let found = bidirectional::solve_bidirectional(&bo, goal, 6);
+		prop_assert_eq!(found.as_ref().map(|moves| moves.len()), expected);
+		if let Some(moves) = found {
+			let mut robots = bo.robots;
+			for m in &moves {
+				robots = naive_move(&bo, &robots, m.c, m.d).expect("the move is blocked");
+			}
+			prop_assert_eq!(robots[goal.0], goal.1);
+		}
+	}
+}
+
+#[test]
+fn board_records_default_missing_features() {
+	let record: pool::BoardRecord =
+		serde_json::from_str(CORNERS).expect("the features default to none");
+	let bo = record.to_board().expect("the board is valid");
+	assert!(!bo.torus);
+	assert!(bo.blocked.is_empty() && bo.holes.is_empty() && bo.teleporters.is_empty());
+	assert!(bo.sticky.is_empty() && bo.ice.is_empty());
+}
+
+#[test]
+fn state_space_of_a_single_row() {
+	let bo = row_board();
+	let report = analysis::StateSpaceReport::new(&bo);
+	assert_eq!(report.state_count, 5);
+	assert_eq!(report.depth_histogram, vec![1; 5]);
+	assert_eq!(report.eccentricity, 4);
+	assert_eq!(report.goal_depth, 4);
+	let farthest: Vec<_> = report
+		.farthest_goals
+		.iter()
+		.map(|g| (g.colour, g.y, g.x))
+		.collect();
+	assert_eq!(farthest, vec![(0, 0, 1)]);
+	// Each robot only ever reaches its own cell and the one to the right.
+	assert_eq!(
+		report.unreachable_goals,
+		5 * ROBOTS_COUNT - 2 * ROBOTS_COUNT
+	);
+}
+
+#[test]
+fn bundled_presets_are_valid() {
+	let presets = preset::default_presets();
+	for preset in &presets {
+		assert_eq!(
+			preset.options.check(preset.h, preset.w, preset.wall_num),
+			Ok(()),
+			"{}",
+			preset.name
+		);
+		assert!(preset.options.min_length <= preset.depth as usize);
+	}
+	// The names the bot asks for.
+	for &(alias, name) in &[
+		("ベイビー", "baby"),
+		("スーパー", "super"),
+		("ハイパー", "hyper"),
+	] {
+		let preset = preset::find_preset(&presets, alias).expect("the alias is missing");
+		assert_eq!(preset.name, name);
+		assert_eq!(preset.options.policy, WallPolicy::RandomPairs);
+	}
+}
+
+#[test]
+fn invalid_presets_are_rejected() {
+	let parse = |json: &str| preset::parse_presets(json).map(|presets| presets.len());
+	assert_eq!(
+		parse(r#"[{"name": "a", "height": 3, "width": 3, "walls": 1}]"#),
+		Ok(1)
+	);
+	// Too many robots and holes for the board.
+	assert!(parse(r#"[{"name": "a", "height": 2, "width": 2, "walls": 0, "holes": 1}]"#).is_err());
+	assert!(parse(r#"[{"name": "a", "height": 3, "width": 3, "walls": 1, "robots": 5}]"#).is_err());
+	assert!(parse(
+		r#"[{"name": "a", "height": 3, "width": 3, "walls": 1, "depth": {"min": 5, "max": 4}}]"#
+	)
+	.is_err());
+	assert!(
+		parse(r#"[{"name": "a", "height": 3, "width": 3, "walls": 1, "policy": "spiral"}]"#)
+			.is_err()
+	);
+	assert!(parse(r#"[{"name": "a", "height": 3, "width": 3, "walls": 1, "colour": 1}]"#).is_err());
+	assert!(parse(
+		r#"[{"name": "a", "height": 3, "width": 3, "walls": 1}, {"name": "b", "aliases": ["a"], "height": 3, "width": 3, "walls": 1}]"#
+	)
+	.is_err());
+}
+
+#[test]
+fn difficulty_counts_blockers() {
+	let weights = DifficultyWeights::default();
+	let row = row_board();
+	let goal = ROW_GOAL;
+	assert_eq!(solve(&row, goal, 5).map(|moves| moves.len()), Some(4));
+	let report = DifficultyReport::new(&row, goal, 4, &weights);
+	assert_eq!(report.robots_moved, 4);
+	assert_eq!(report.blocker_moves, 3);
+	assert_eq!(report.blocker_stops, 1);
+	assert_eq!(report.solution_count, 1);
+	assert_eq!(report.frontier_size, 1);
+
+	let report = DifficultyReport::new(&row, (3, Pos { y: 0, x: 4 }), 1, &weights);
+	assert_eq!(report.robots_moved, 1);
+	assert_eq!(report.blocker_moves, 0);
+	// Stopped by the border.
+	assert_eq!(report.blocker_stops, 0);
+
+	// Robot 0 stops next to robot 1 without moving it.
+	let corners = corners_board();
+	let report = DifficultyReport::new(&corners, (0, Pos { y: 0, x: 1 }), 1, &weights);
+	assert_eq!(report.robots_moved, 1);
+	assert_eq!(report.blocker_moves, 0);
+	assert_eq!(report.blocker_stops, 1);
+	assert_eq!(report.solution_count, 1);
+}
+
+#[test]
+fn solve_with_cost_weighs_the_moves() {
+	let row = row_board();
+	let goal = ROW_GOAL;
+	let cost_of = |model: CostModel| {
+		cost::solve_with_cost(&row, goal, &model, 100).map(|report| {
+			assert_eq!(report.moves, 4);
+			assert_eq!(report.robot_selections, 4);
+			assert_eq!((report.goal_moves, report.other_moves), (1, 3));
+			report.cost
+		})
+	};
+	assert_eq!(cost_of(CostModel::Moves), Some(4));
+	assert_eq!(cost_of(CostModel::RobotSelections), Some(4));
+	assert_eq!(cost_of(CostModel::Weighted { goal: 0, other: 1 }), Some(3));
+	assert_eq!(
+		cost_of(CostModel::Weighted { goal: 10, other: 1 }),
+		Some(13)
+	);
+	assert!(
+		cost::solve_with_cost(&row, goal, &CostModel::Weighted { goal: 10, other: 1 }, 12)
+			.is_none()
+	);
+	// The sums of huge weights don't overflow.
+	let huge = CostModel::Weighted {
+		goal: u32::MAX,
+		other: u32::MAX,
+	};
+	assert!(cost::solve_with_cost(&row, goal, &huge, u32::MAX).is_none());
+
+	// Moving the same robot twice is a single selection.
+	let corners = corners_board();
+	let report = cost::solve_with_cost(
+		&corners,
+		(0, Pos { y: 1, x: 2 }),
+		&CostModel::RobotSelections,
+		100,
+	)
+	.expect("the goal is reachable");
+	// The goal robot stops above the robot below it and then slides right.
+	assert_eq!((report.cost, report.moves), (1, 2));
+}
+
+#[test]
+fn evaluate_answer_finds_the_first_deviation() {
+	let row = row_board();
+	let goal = ROW_GOAL;
+	let moves =
+		|ms: &[(usize, usize)]| -> Vec<Move> { ms.iter().map(|&(c, d)| Move { c, d }).collect() };
+
+	let res = evaluate::evaluate_answer(&row, goal, 4, &moves(&[(3, 1), (2, 1), (1, 1), (0, 1)]));
+	assert!(res.solved);
+	assert_eq!(res.moves, 4);
+	assert_eq!(res.goal_robot, Some(goal.1));
+	assert_eq!(res.remaining, Some(0));
+	assert_eq!(res.first_deviation, None);
+
+	// A blocked move leaves the board as it is, which is already too slow.
+	let res = evaluate::evaluate_answer(&row, goal, 4, &moves(&[(0, 3)]));
+	assert!(!res.solved);
+	assert_eq!(res.goal_robot, Some(Pos { y: 0, x: 0 }));
+	assert_eq!(res.remaining, Some(4));
+	assert_eq!(res.first_deviation, Some(0));
+
+	let res = evaluate::evaluate_answer(&row, goal, 4, &moves(&[(3, 1), (2, 1), (3, 3), (1, 1)]));
+	assert!(!res.solved);
+	assert_eq!(res.remaining, Some(1));
+	assert_eq!(res.first_deviation, Some(2));
+
+	// Moving a robot back and forth still reaches the goal, but too late.
+	let res = evaluate::evaluate_answer(
+		&row,
+		goal,
+		4,
+		&moves(&[(3, 1), (3, 3), (3, 1), (2, 1), (1, 1), (0, 1)]),
+	);
+	assert!(res.solved);
+	assert_eq!(res.first_deviation, Some(1));
+}
+
+#[test]
+fn parse_errors_point_at_the_character() {
+	let error = |s: &str| notation::parse_command(s).unwrap_err();
+	let expect =
+		|position: usize, expected: &'static str, found: Option<char>| notation::ParseError {
+			position,
+			expected,
+			found,
+		};
+
+	assert_eq!(error(""), expect(0, "a colour", None));
+	assert_eq!(error("x下"), expect(0, "a colour", Some('x')));
+	assert_eq!(error("赤x"), expect(1, "a direction", Some('x')));
+	assert_eq!(error("赤下青"), expect(3, "a direction", None));
+	// Positions count characters, not the bytes of the multi-byte names.
+	assert_eq!(
+		error("赤下,"),
+		expect(2, "a colour, a direction or まで", Some(','))
+	);
+	assert_eq!(
+		error("rsま"),
+		expect(2, "a colour, a direction or まで", Some('ま'))
+	);
+	assert_eq!(
+		error("赤下まで下"),
+		expect(4, "the end after まで", Some('下'))
+	);
+
+	assert_eq!(
+		error("赤下,").to_string(),
+		"expected a colour, a direction or まで at character 3, found ','"
+	);
+	assert_eq!(
+		error("赤下青").to_string(),
+		"expected a direction at the end"
+	);
+}
+
+#[test]
+fn seed_zero_generates_a_board() {
+	let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+	let problem = generate_problem(
+		16,
+		16,
+		20,
+		&BoardOptions::default(),
+		8,
+		seed_states(0),
+		&mut rng,
+	);
+	assert!(problem.is_ok(), "{:?}", problem.err());
+}
+
+#[test]
+fn generate_pool_gives_up_on_impossible_presets() {
+	let presets = preset::parse_presets(
+		r#"[{"name": "full", "height": 2, "width": 2, "walls": 0}, {"name": "small", "height": 3, "width": 3, "walls": 1}]"#,
+	)
+	.unwrap();
+	let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+	let mut seen = HashSet::new();
+	// The robots fill the board, so no robot can move.
+	let res = pool::generate_pool(&presets[0], 2, &mut seen, &mut rng);
+	assert_eq!(
+		res.err().as_deref(),
+		Some("only 0 of 2 distinct puzzles of full in 200 boards")
+	);
+
+	let res = pool::generate_pool(&presets[1], 5, &mut seen, &mut rng).unwrap();
+	assert_eq!(res.len(), 5);
+	assert_eq!(seen.len(), 5);
+}
@@ -0,0 +1,137 @@
+//! WebAssembly bindings of the ricochet robots engine for playing in the browser.
+//! Boards, goals and moves are passed in the shapes of `BoardRecord`, `GoalRecord` and `Move`.
+//! Build with `cargo build -p ricochet-robots-wasm --target wasm32-unknown-unknown --release`
+//! and generate the JS glue with `wasm-bindgen`.
+
+extern crate wasm_bindgen;
+use wasm_bindgen::prelude::*;
+
+extern crate serde_wasm_bindgen;
+
+extern crate ricochet_robots;
+use ricochet_robots::evaluate::evaluate_answer;
+use ricochet_robots::notation::{self, parse_command};
+use ricochet_robots::pool::{BoardRecord, GoalRecord};
+use ricochet_robots::{Board, Move, Pos, ROBOTS_COUNT};
+
+fn from_js<T: serde::de::DeserializeOwned>(value: JsValue) -> Result<T, JsValue> {
+	serde_wasm_bindgen::from_value(value).map_err(JsValue::from)
+}
+
+fn to_js<T: serde::Serialize>(value: &T) -> Result<JsValue, JsValue> {
+	serde_wasm_bindgen::to_value(value).map_err(JsValue::from)
+}
+
+fn check_move(c: usize, d: usize) -> Result<Move, JsValue> {
+	if c < ROBOTS_COUNT && d < 4 {
+		Ok(Move { c, d })
+	} else {
+		Err(JsValue::from_str(&format!("invalid move ({}, {})", c, d)))
+	}
+}
+
+fn goal_from_js(goal: JsValue) -> Result<(usize, Pos), JsValue> {
+	let goal: GoalRecord = from_js(goal)?;
+	if goal.colour >= ROBOTS_COUNT {
+		return Err(JsValue::from_str(&format!(
+			"invalid colour {}",
+			goal.colour
+		)));
+	}
+	Ok((
+		goal.colour,
+		Pos {
+			y: goal.y,
+			x: goal.x,
+		},
+	))
+}
+
+/// A board being played on. The robots move as the player moves them.
+#[wasm_bindgen]
+pub struct Game {
+	board: Board,
+}
+
+#[wasm_bindgen]
+impl Game {
+	#[wasm_bindgen(constructor)]
+	pub fn new(board: JsValue) -> Result<Game, JsValue> {
+		let record: BoardRecord = from_js(board)?;
+		let board = record.to_board().map_err(|e| JsValue::from_str(&e))?;
+		Ok(Game { board })
+	}
+
+	/// The positions of the robots. Robots which fell into a hole are `null`.
+	pub fn robots(&self) -> Result<JsValue, JsValue> {
+		let robots: Vec<Option<Pos>> = (0..ROBOTS_COUNT).map(|i| self.board.robot(i)).collect();
+		to_js(&robots)
+	}
+
+	#[wasm_bindgen(js_name = legalMoves)]
+	pub fn legal_moves(&self) -> Result<JsValue, JsValue> {
+		to_js(&self.board.legal_moves())
+	}
+
+	/// Moves the robot `c` in the direction `d`. Returns whether it moved.
+	#[wasm_bindgen(js_name = moveRobot)]
+	pub fn move_robot(&mut self, c: usize, d: usize) -> Result<bool, JsValue> {
+		let m = check_move(c, d)?;
+		Ok(self.board.apply_move(&m))
+	}
+
+	#[wasm_bindgen(js_name = isSolved)]
+	pub fn is_solved(&self, goal: JsValue) -> Result<bool, JsValue> {
+		let goal = goal_from_js(goal)?;
+		Ok(self.board.robot(goal.0) == Some(goal.1))
+	}
+
+	/// The shortest answer from the current position within `max_depth` moves, or `null`.
+	pub fn solve(&self, goal: JsValue, max_depth: usize) -> Result<JsValue, JsValue> {
+		let goal = goal_from_js(goal)?;
+		let answer = ricochet_robots::solve(&self.board, goal, max_depth);
+		to_js(&answer)
+	}
+
+	/// The first move of the shortest answer from the current position, or `null`.
+	pub fn hint(&self, goal: JsValue, max_depth: usize) -> Result<JsValue, JsValue> {
+		let goal = goal_from_js(goal)?;
+		let answer = ricochet_robots::solve(&self.board, goal, max_depth);
+		to_js(&answer.and_then(|moves| moves.first().cloned()))
+	}
+
+	/// Replays `moves` from the current position like `evaluate_answer`.
+	pub fn verify(
+		&self,
+		goal: JsValue,
+		optimal_length: usize,
+		moves: JsValue,
+	) -> Result<JsValue, JsValue> {
+		let goal = goal_from_js(goal)?;
+		let moves: Vec<Move> = from_js(moves)?;
+		let moves = moves
+			.iter()
+			.map(|m| check_move(m.c, m.d))
+			.collect::<Result<Vec<_>, _>>()?;
+		let evaluation = evaluate_answer(&self.board, goal, optimal_length, &moves);
+		to_js(&evaluation)
+	}
+}
+
+/// Parses an answer as players type it, like `赤下右青上まで`, into `{moves, isMADE}`.
+#[wasm_bindgen(js_name = parseAnswer)]
+pub fn parse_answer(text: &str) -> Result<JsValue, JsValue> {
+	let command = parse_command(text).map_err(|e| JsValue::from_str(&e.to_string()))?;
+	to_js(&command)
+}
+
+/// Writes the moves like `logstringfy`, e.g. `赤下,青左`.
+#[wasm_bindgen(js_name = formatMoves)]
+pub fn format_moves(moves: JsValue) -> Result<String, JsValue> {
+	let moves: Vec<Move> = from_js(moves)?;
+	let moves = moves
+		.iter()
+		.map(|m| check_move(m.c, m.d))
+		.collect::<Result<Vec<_>, _>>()?;
+	Ok(notation::format_moves(&moves))
+}
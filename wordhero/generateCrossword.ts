@@ -3,45 +3,73 @@ import sqlite3 from 'sqlite3';
 import path from 'path';
 import {spawn} from 'child_process';
 import concat from 'concat-stream';
-import {sortBy} from 'lodash';
-import boardConfigs from './boards.json';
 import type {Crossword} from './crossword';
 
-const stocks: any[] = [];
+// The output of `crossword_generator_main --format json`
+export interface GeneratedBoard {
+	layoutId: string,
+	width: number,
+	height: number,
+	grid: (string | null)[][],
+	entries: {
+		word: string,
+		cells: {x: number, y: number}[],
+		direction: 'horizontal' | 'diagonal' | 'vertical',
+		clue: number,
+	}[],
+}
 
-// 6x6 format to 20x20 format
-const convertToNewFormat = (board: string[]) => (
-	Array(400).fill(null).map((e, i) => {
-		const x = i % 20;
-		const y = Math.floor(i / 20);
-		if (x < 6 && y < 6 && board[y * 6 + x] !== undefined) {
-			return board[y * 6 + x];
-		}
-		return null;
-	})
-);
+// Cells are numbered row by row on a 20x20 board.
+export const toCellIndex = ({x, y}: {x: number, y: number}) => y * 20 + x;
 
-const generate = async (usedAt: string): Promise<Crossword> => {
-	if (stocks.length === 0) {
-		const generator = spawn('target/release/crossword_generator_main', {cwd: process.cwd()});
-		const output = await new Promise<Buffer>((resolve) => {
+// The search of a dense layout may take long with an unlucky dictionary, so the generator is killed after this.
+const GENERATOR_TIMEOUT = 3 * 60 * 1000;
+
+// Each run of the generator loads the dictionary again, so boards are filled this many at a time.
+const STOCK_SIZE = 10;
+
+// Boards filled ahead, by the arguments of the generator.
+const stocks = new Map<string, GeneratedBoard[]>();
+
+// Fills `count` boards from the given board configs, or from the bundled boards.json by default.
+const fillBoards = async (args: string[], count: number): Promise<GeneratedBoard[]> => {
+	const generator = spawn('target/release/crossword_generator_main', [...args, '--count', count.toString(), '--format', 'json'], {
+		cwd: process.cwd(),
+		timeout: GENERATOR_TIMEOUT,
+	});
+	const [output, [code, signal]] = await Promise.all([
+		new Promise<Buffer>((resolve) => {
 			generator.stdout.pipe(concat({encoding: 'buffer'}, (data: Buffer) => {
 				resolve(data);
 			}));
-		});
+		}),
+		new Promise<[number | null, NodeJS.Signals | null]>((resolve) => {
+			generator.on('close', (code, signal) => resolve([code, signal]));
+		}),
+	]);
+	if (signal !== null) {
+		throw new Error(`crossword_generator_main was killed by ${signal} (timeout ${GENERATOR_TIMEOUT / 1000}s)`);
+	}
+	if (code !== 0) {
+		throw new Error(`crossword_generator_main failed (exit code ${code})`);
+	}
+	return output.toString().split('\n').filter((line) => line).map((line) => JSON.parse(line));
+};
 
-		const lines = output.toString().split('\n').filter((line) => line);
-		for (const line of lines) {
-			const [index, board] = line.split(',');
-			stocks.push({index: parseInt(index), board: board.split('').map((char) => char === '　' ? null : char)});
-		}
+// Takes a board filled with the given arguments of the generator, filling a new stock when it runs out.
+export const fillBoard = async (args: string[] = []): Promise<GeneratedBoard> => {
+	const key = args.join(' ');
+	let stock = stocks.get(key);
+	if (stock === undefined || stock.length === 0) {
+		stock = await fillBoards(args, STOCK_SIZE);
+		stocks.set(key, stock);
 	}
+	return stock.shift()!;
+};
 
-	const {index, board} = stocks.shift();
-	const constraints = boardConfigs[index];
-	const words = sortBy(constraints, ({index}) => index).map(({cells}) => (
-		cells.map((cell) => board[cell]).join('')
-	));
+const generate = async (usedAt: string): Promise<Crossword> => {
+	const {layoutId, grid, entries} = await fillBoard();
+	const words = entries.map(({word}) => word);
 
 	const db = await sqlite.open({
 		filename: path.join(__dirname, 'crossword.sqlite3'),
@@ -55,17 +83,13 @@ const generate = async (usedAt: string): Promise<Crossword> => {
 		words,
 		descriptions: descriptions.map((description, index) => ({
 			...description,
-			descriptionId: (index + 1).toString(),
+			descriptionId: entries[index].clue.toString(),
 		})),
-		board: convertToNewFormat(board),
-		boardId: `crossword-board-${index + 1}`,
-		constraints: constraints.map((constraint) => ({
-			cells: constraint.cells.map((cell) => {
-				const x = cell % 6;
-				const y = Math.floor(cell / 6);
-				return y * 20 + x;
-			}),
-			descriptionId: constraint.index.toString(),
+		board: Array(400).fill(null).map((e, i) => grid[Math.floor(i / 20)]?.[i % 20] ?? null),
+		boardId: layoutId,
+		constraints: entries.map(({cells, clue}) => ({
+			cells: cells.map(toCellIndex),
+			descriptionId: clue.toString(),
 		})),
 	};
 };
@@ -4,6 +4,7 @@ import path from 'path';
 import db from '../lib/firestore';
 import {sample, negate, isEmpty, maxBy} from 'lodash';
 import type {Crossword} from './crossword';
+import {fillBoard, toCellIndex} from './generateCrossword';
 
 const Boards = db.collection('crossword_boards');
 
@@ -122,6 +123,35 @@ export const parseBoard = (board: string) => {
 };
 
 
+// Fills one of the letter-shaped layouts in grosswords.json.
+const generateFromLayouts = async (): Promise<Crossword> => {
+	const {layoutId, grid, entries} = await fillBoard(['--boards', path.join(__dirname, 'grosswords.json')]);
+	const words = entries.map(({word}) => word);
+	const constraints = entries.map(({cells, direction, clue}) => ({
+		cells: cells.map(toCellIndex),
+		descriptionId: `${direction === 'vertical' ? 'タテ' : 'ヨコ'}${clue}`,
+	}));
+
+	const crosswordDb = await sqlite.open({
+		filename: path.join(__dirname, 'crossword.sqlite3'),
+		driver: sqlite3.Database,
+	});
+	const descriptions = await Promise.all(words.map((word) => (
+		crosswordDb.get('SELECT * FROM words WHERE ruby = ? ORDER BY RANDOM() LIMIT 1', word)
+	)));
+
+	return {
+		words,
+		descriptions: descriptions.map((description, index) => ({
+			...description,
+			descriptionId: constraints[index].descriptionId,
+		})),
+		board: Array(400).fill(null).map((e, i) => grid[Math.floor(i / 20)]?.[i % 20] ?? null),
+		boardId: layoutId,
+		constraints,
+	};
+};
+
 const generate = async (usedAt: string): Promise<Crossword> => {
 	const crosswordData = await db.runTransaction(async (transaction) => {
 		const query = Boards.where('category', '==', 'grossword').where('used_at', '==', null);
@@ -137,8 +167,9 @@ const generate = async (usedAt: string): Promise<Crossword> => {
 		return crossword.data();
 	});
 
+	// The hand-made boards ran out.
 	if (crosswordData === null) {
-		return null;
+		return generateFromLayouts();
 	}
 
 	const {board, constraints} = parseBoard(crosswordData.board);
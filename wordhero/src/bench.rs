@@ -23,7 +23,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             trie.predictive_search("すし");
         }
         let end = start.elapsed();
-        println!("trie_rs: {}.{:03}s", end.as_secs(), end.subsec_nanos() / 1_000_000);
+        println!("trie_rs: {}.{:03}s", end.as_secs(), end.subsec_millis());
     }
 
     {
@@ -47,7 +47,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             trie.predictive_search([24, 22]);
         }
         let end = start.elapsed();
-        println!("trie_rs (indices): {}.{:03}s", end.as_secs(), end.subsec_nanos() / 1_000_000);
+        println!("trie_rs (indices): {}.{:03}s", end.as_secs(), end.subsec_millis());
     }
 
     {
@@ -62,7 +62,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             trie.find("すし").next();
         }
         let end = start.elapsed();
-        println!("radix_trie: {}.{:03}s", end.as_secs(), end.subsec_nanos() / 1_000_000);
+        println!("radix_trie: {}.{:03}s", end.as_secs(), end.subsec_millis());
     }
 
     {
@@ -83,7 +83,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             stream.next();
         }
         let end = start.elapsed();
-        println!("fst (range): {}.{:03}s", end.as_secs(), end.subsec_nanos() / 1_000_000);
+        println!("fst (range): {}.{:03}s", end.as_secs(), end.subsec_millis());
     }
 
     {
@@ -99,7 +99,7 @@ fn main() -> Result<(), Box<dyn Error>> {
             stream.next();
         }
         let end = start.elapsed();
-        println!("fst (regex): {}.{:03}s", end.as_secs(), end.subsec_nanos() / 1_000_000);
+        println!("fst (regex): {}.{:03}s", end.as_secs(), end.subsec_millis());
     }
 
     {
@@ -126,14 +126,14 @@ fn main() -> Result<(), Box<dyn Error>> {
             stream.next();
         }
         let end = start.elapsed();
-        println!("fst (indices, range): {}.{:03}s", end.as_secs(), end.subsec_nanos() / 1_000_000);
+        println!("fst (indices, range): {}.{:03}s", end.as_secs(), end.subsec_millis());
 
         let start = Instant::now();
         for _i in 0..10_000 {
             set.range().ge([24]).lt([25]).into_stream().into_strs()?.len();
         }
         let end = start.elapsed();
-        println!("fst (indices, range, count): {}.{:03}s", end.as_secs(), end.subsec_nanos() / 1_000_000);
+        println!("fst (indices, range, count): {}.{:03}s", end.as_secs(), end.subsec_millis());
     }
 
     Ok(())
@@ -1,17 +1,17 @@
 #[macro_use]
 extern crate lazy_static;
 
-use std::{io, fs};
+use std::{env, io, fs};
 use std::io::BufRead;
 use std::iter::FromIterator;
 use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use fst::{IntoStreamer, Streamer, Set};
-use rand::thread_rng;
-use rand::Rng;
+use getopts::Options;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use serde_derive::{Deserialize};
-use serde_json;
+use serde_derive::{Deserialize, Serialize};
 use serde_repr::Deserialize_repr;
 
 const HIRAGANAS: [char; 81] = ['ぁ', 'あ', 'ぃ', 'い', 'ぅ', 'う', 'ぇ', 'え', 'ぉ', 'お', 'か', 'が', 'き', 'ぎ', 'く', 'ぐ', 'け', 'げ', 'こ', 'ご', 'さ', 'ざ', 'し', 'じ', 'す', 'ず', 'せ', 'ぜ', 'そ', 'ぞ', 'た', 'だ', 'ち', 'ぢ', 'っ', 'つ', 'づ', 'て', 'で', 'と', 'ど', 'な', 'に', 'ぬ', 'ね', 'の', 'は', 'ば', 'ぱ', 'ひ', 'び', 'ぴ', 'ふ', 'ぶ', 'ぷ', 'へ', 'べ', 'ぺ', 'ほ', 'ぼ', 'ぽ', 'ま', 'み', 'む', 'め', 'も', 'ゃ', 'や', 'ゅ', 'ゆ', 'ょ', 'よ', 'ら', 'り', 'る', 'れ', 'ろ', 'わ', 'を', 'ん', 'ー'];
@@ -27,7 +27,8 @@ lazy_static! {
     };
 }
 
-#[derive(PartialEq, Deserialize_repr, Debug, Clone)]
+#[derive(PartialEq, Deserialize_repr, Serialize, Debug, Clone)]
+#[serde(rename_all = "lowercase")]
 #[repr(u8)]
 enum Direction {
     Horizontal = 0,
@@ -39,6 +40,83 @@ enum Direction {
 struct Constraint {
     direction: Direction,
     cells: Vec<usize>,
+    /// The clue number of the word.
+    index: usize,
+}
+
+/// The shortest word on a board.
+const MIN_WORD_LEN: usize = 3;
+
+/// The largest width and height of a board, which is also the longest word.
+const MAX_BOARD_SIZE: usize = 20;
+
+/// The size of the boards given as bare lists of constraints, like the ones in `boards.json`.
+const DEFAULT_BOARD_SIZE: usize = 6;
+
+/// A board config as written in the file: either a bare list of constraints on a 6x6 board,
+/// or an object with the size of the board.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(untagged)]
+enum BoardConfigFile {
+    Sized {
+        width: usize,
+        height: usize,
+        #[serde(rename = "layoutId")]
+        layout_id: Option<String>,
+        constraints: Vec<Constraint>,
+    },
+    Bare(Vec<Constraint>),
+}
+
+/// The cells of the board are numbered row by row, `y * width + x`.
+#[derive(Debug, Clone)]
+struct BoardConfig {
+    width: usize,
+    height: usize,
+    /// The name of the board image. Defaults to `crossword-board-<config + 1>`.
+    layout_id: Option<String>,
+    constraints: Vec<Constraint>,
+}
+
+impl From<BoardConfigFile> for BoardConfig {
+    fn from(config: BoardConfigFile) -> Self {
+        match config {
+            BoardConfigFile::Sized { width, height, layout_id, constraints } => BoardConfig { width, height, layout_id, constraints },
+            BoardConfigFile::Bare(constraints) => BoardConfig {
+                width: DEFAULT_BOARD_SIZE,
+                height: DEFAULT_BOARD_SIZE,
+                layout_id: None,
+                constraints,
+            },
+        }
+    }
+}
+
+impl BoardConfig {
+    fn check(&self) -> Result<(), String> {
+        if !(1..=MAX_BOARD_SIZE).contains(&self.width) || !(1..=MAX_BOARD_SIZE).contains(&self.height) {
+            return Err(format!("the board is {}x{}, but it must fit in {}x{}", self.width, self.height, MAX_BOARD_SIZE, MAX_BOARD_SIZE));
+        }
+        for constraint in &self.constraints {
+            if !(MIN_WORD_LEN..=MAX_BOARD_SIZE).contains(&constraint.cells.len()) {
+                return Err(format!("word {} has {} cells, but words have {} to {}", constraint.index, constraint.cells.len(), MIN_WORD_LEN, MAX_BOARD_SIZE));
+            }
+            if let Some(&cell) = constraint.cells.iter().find(|&&cell| cell >= self.width * self.height) {
+                return Err(format!("word {} has the cell {} outside the board", constraint.index, cell));
+            }
+        }
+        // `get_board` only fills the horizontal and vertical words, and checks the diagonal ones against them.
+        let filled: HashSet<usize> = self.constraints.iter()
+            .filter(|constraint| constraint.direction != Direction::Diagonal)
+            .flat_map(|constraint| constraint.cells.iter().cloned())
+            .collect();
+        for constraint in self.constraints.iter().filter(|constraint| constraint.direction == Direction::Diagonal) {
+            if let Some(&cell) = constraint.cells.iter().find(|cell| !filled.contains(cell)) {
+                return Err(format!("diagonal word {} has the cell {} outside the horizontal and vertical words", constraint.index, cell));
+            }
+        }
+        Ok(())
+    }
 }
 
 struct Context<'a> {
@@ -65,10 +143,14 @@ fn bytes_to_string(bytes: Vec<u8>) -> String {
 fn has_prefix(set: &Set, prefix: Vec<u8>) -> bool {
     let mut prefix_stop = prefix.clone();
     *prefix_stop.last_mut().unwrap() += 1;
-    set.range().ge(prefix).lt(prefix_stop).into_stream().next() != None
+    set.range().ge(prefix).lt(prefix_stop).into_stream().next().is_some()
 }
 
 fn get_prefix(set: &Set, prefix: Vec<u8>) -> Vec<Vec<u8>> {
+    // A word which doesn't cross the filled cells yet can be any word.
+    if prefix.is_empty() {
+        return set.stream().into_bytes();
+    }
     let mut prefix_stop = prefix.clone();
     *prefix_stop.last_mut().unwrap() += 1;
     set.range().ge(prefix).lt(prefix_stop).into_stream().into_bytes()
@@ -80,13 +162,33 @@ fn count_prefix(set: &Set, prefix: Vec<u8>) -> usize {
     set.range().ge(prefix).lt(prefix_stop).into_stream().into_bytes().len()
 }
 
-fn get_board(context: &Context, board: [u8; 36]) -> Option<[u8; 36]> {
+/// Sorts the words of each length from `MIN_WORD_LEN` to `MAX_BOARD_SIZE` into a set.
+fn word_sets(mut word_lists: Vec<Vec<Vec<u8>>>) -> Vec<Set> {
+    word_lists.iter_mut().map(|word_list| {
+        word_list.sort();
+        Set::from_iter(word_list).unwrap()
+    }).collect()
+}
+
+fn two_gram_counters(sets: &[Set]) -> Vec<HashMap<[u8; 2], usize>> {
+    let mut two_gram_counters = vec![HashMap::new(); sets.len()];
+
+    for i in 0..HIRAGANAS.len() {
+        for j in 0..HIRAGANAS.len() {
+            for (two_gram_counter, set) in two_gram_counters.iter_mut().zip(sets.iter()) {
+                two_gram_counter.insert([i as u8, j as u8], count_prefix(set, vec![i as u8, j as u8]));
+            }
+        }
+    }
+
+    two_gram_counters
+}
+
+fn get_board(context: &Context, board: Vec<u8>, rng: &mut StdRng) -> Option<Vec<u8>> {
     if context.cells.iter().all(|&c| board[c] != 0) {
         return Some(board);
     }
 
-    let mut rng = thread_rng();
-
     let vertical_constraints = context.constraints.iter().filter(|&c| c.direction == Direction::Vertical).collect::<Vec<&Constraint>>();
     let horizontal_constraints = context.constraints.iter().filter(|&c| c.direction == Direction::Horizontal).collect::<Vec<&Constraint>>();
 
@@ -114,9 +216,9 @@ fn get_board(context: &Context, board: [u8; 36]) -> Option<[u8; 36]> {
             continue;
         }
 
-        if index == 0 && prefix.len() == 0 {
+        if index == 0 && prefix.is_empty() {
             let mut new_values: Vec<u8> = (1..(HIRAGANAS.len() as u8 + 1)).collect();
-            new_values.shuffle(&mut rng);
+            new_values.shuffle(rng);
             for new_value in new_values {
                 if is_non_initial(new_value) {
                     continue;
@@ -127,7 +229,7 @@ fn get_board(context: &Context, board: [u8; 36]) -> Option<[u8; 36]> {
 
                 // no need to check constraints
 
-                let result = get_board(context, cloned_board);
+                let result = get_board(context, cloned_board, rng);
                 if let Some(board) = result {
                     return Some(board);
                 }
@@ -138,13 +240,13 @@ fn get_board(context: &Context, board: [u8; 36]) -> Option<[u8; 36]> {
 
         if index == 0 && prefix.len() == 1 {
             let mut new_values: Vec<u8> = (1..(HIRAGANAS.len() as u8 + 1)).collect();
-            new_values.shuffle(&mut rng);
+            new_values.shuffle(rng);
             for new_value in new_values {
                 if is_non_initial(new_value) {
                     continue;
                 }
 
-                if let Some(&count) = context.two_gram_counters[constraint.cells.len() - 3].get(&[prefix[0], new_value]) {
+                if let Some(&count) = context.two_gram_counters[constraint.cells.len() - MIN_WORD_LEN].get(&[prefix[0], new_value]) {
                     if count < 1 {
                         continue;
                     }
@@ -155,7 +257,7 @@ fn get_board(context: &Context, board: [u8; 36]) -> Option<[u8; 36]> {
 
                 // no need to check constraints
 
-                let result = get_board(context, cloned_board);
+                let result = get_board(context, cloned_board, rng);
                 if let Some(board) = result {
                     return Some(board);
                 }
@@ -165,7 +267,7 @@ fn get_board(context: &Context, board: [u8; 36]) -> Option<[u8; 36]> {
 
         if index == 1 && prefix.len() == 1 {
             let mut new_values: Vec<u8> = (1..(HIRAGANAS.len() as u8 + 1)).collect();
-            new_values.shuffle(&mut rng);
+            new_values.shuffle(rng);
             'second_values: for new_value in new_values {
                 let mut cloned_board = board.clone();
                 cloned_board[constraint.cells[1]] = new_value;
@@ -176,15 +278,15 @@ fn get_board(context: &Context, board: [u8; 36]) -> Option<[u8; 36]> {
                         continue 'second_values;
                     }
                     let prefix: Vec<u8> = constraint.cells.iter().map(|&c| cloned_board[c]).take_while(|&c| c != 0).collect();
-                    if prefix.len() == 0 {
+                    if prefix.is_empty() {
                         continue;
                     }
-                    if !has_prefix(&context.sets[constraint.cells.len() - 3], prefix) {
+                    if !has_prefix(&context.sets[constraint.cells.len() - MIN_WORD_LEN], prefix) {
                         continue 'second_values;
                     }
                 }
 
-                let result = get_board(context, cloned_board);
+                let result = get_board(context, cloned_board, rng);
                 if let Some(board) = result {
                     return Some(board);
                 }
@@ -192,13 +294,11 @@ fn get_board(context: &Context, board: [u8; 36]) -> Option<[u8; 36]> {
             break;
         }
 
-        let mut words = get_prefix(&context.sets[constraint.cells.len() - 3], prefix.to_vec());
-        words.shuffle(&mut rng);
+        let mut words = get_prefix(&context.sets[constraint.cells.len() - MIN_WORD_LEN], prefix.to_vec());
+        words.shuffle(rng);
         'words: for word in words {
-            if index == 0 {
-                if word.iter().any(|&c| is_non_initial(c)) {
-                    continue;
-                }
+            if index == 0 && word.iter().any(|&c| is_non_initial(c)) {
+                continue;
             }
 
             let mut cloned_board = board.clone();
@@ -212,15 +312,15 @@ fn get_board(context: &Context, board: [u8; 36]) -> Option<[u8; 36]> {
                     continue 'words;
                 }
                 let prefix: Vec<u8> = constraint.cells.iter().map(|&c| cloned_board[c]).take_while(|&c| c != 0).collect();
-                if prefix.len() == 0 {
+                if prefix.is_empty() {
                     continue;
                 }
-                if !has_prefix(&context.sets[constraint.cells.len() - 3], prefix) {
+                if !has_prefix(&context.sets[constraint.cells.len() - MIN_WORD_LEN], prefix) {
                     continue 'words;
                 }
             }
 
-            let result = get_board(context, cloned_board);
+            let result = get_board(context, cloned_board, rng);
             if let Some(board) = result {
                 return Some(board);
             }
@@ -232,13 +332,133 @@ fn get_board(context: &Context, board: [u8; 36]) -> Option<[u8; 36]> {
     None
 }
 
+/// How many times a random layout is tried for each board before giving up.
+const TRIES_PER_BOARD: usize = 10;
+
+/// A filled board as written by `--format json`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BoardRecord {
+    /// The name of the board image, like `crossword-board-1` for config 0 if the config doesn't name it.
+    layout_id: String,
+    config: usize,
+    width: usize,
+    height: usize,
+    /// The rows of the board, with `null` for blank cells.
+    grid: Vec<Vec<Option<char>>>,
+    /// The words in the order of their clue numbers.
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize)]
+struct Entry {
+    word: String,
+    cells: Vec<CellPos>,
+    direction: Direction,
+    clue: usize,
+}
+
+#[derive(Serialize)]
+struct CellPos {
+    x: usize,
+    y: usize,
+}
+
+impl BoardRecord {
+    fn new(config_index: usize, config: &BoardConfig, board: &[u8]) -> Self {
+        let width = config.width;
+        let letter = |cell: usize| match board[cell] {
+            0 => None,
+            i => Some(HIRAGANAS[(i - 1) as usize]),
+        };
+        let grid = (0..config.height)
+            .map(|y| (0..width).map(|x| letter(y * width + x)).collect())
+            .collect();
+        let mut entries: Vec<Entry> = config.constraints.iter().map(|constraint| Entry {
+            word: bytes_to_string(constraint.cells.iter().map(|&cell| board[cell]).collect()),
+            cells: constraint.cells.iter().map(|&cell| CellPos {
+                x: cell % width,
+                y: cell / width,
+            }).collect(),
+            direction: constraint.direction.clone(),
+            clue: constraint.index,
+        }).collect();
+        entries.sort_by_key(|entry| entry.clue);
+        BoardRecord {
+            layout_id: config.layout_id.clone().unwrap_or_else(|| format!("crossword-board-{}", config_index + 1)),
+            config: config_index,
+            width,
+            height: config.height,
+            grid,
+            entries,
+        }
+    }
+}
+
+fn print_usage(opts: &Options) {
+    let brief = "usage: crossword_generator_main [options]";
+    print!("{}", opts.usage(brief));
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
-    let board_configs: Vec<Vec<Constraint>> = serde_json::from_str(include_str!("../boards.json"))?;
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut opts = Options::new();
+    opts.optopt("d", "dictionary", "the word list, one word per line (default crossword.txt)", "PATH");
+    opts.optopt("b", "boards", "the board configs (defaults to the bundled boards.json)", "PATH");
+    opts.optopt("c", "config", "the index of the board config to fill (default random)", "N");
+    opts.optopt("n", "count", "the number of boards to fill (default 10)", "N");
+    opts.optopt("", "seed", "the seed of the random choices (default random)", "N");
+    opts.optopt("", "format", "text (default) or json", "FORMAT");
+    opts.optflag("h", "help", "print this help");
+    let matches = opts.parse(&args)?;
+    if matches.opt_present("help") {
+        print_usage(&opts);
+        return Ok(());
+    }
+
+    let board_configs: Vec<BoardConfigFile> = match matches.opt_str("boards") {
+        Some(path) => serde_json::from_str(&fs::read_to_string(path)?)?,
+        None => serde_json::from_str(include_str!("../boards.json"))?,
+    };
+    let board_configs: Vec<BoardConfig> = board_configs.into_iter().map(BoardConfig::from).collect();
+    if board_configs.is_empty() {
+        return Err("there are no board configs".into());
+    }
+    for (index, board_config) in board_configs.iter().enumerate() {
+        board_config.check().map_err(|e| format!("config {}: {}", index, e))?;
+    }
+    let config: Option<usize> = matches.opt_get("config")?;
+    if let Some(config) = config {
+        if config >= board_configs.len() {
+            return Err(format!("config {} is out of range, there are {} configs", config, board_configs.len()).into());
+        }
+    }
+    let count: usize = matches.opt_get_default("count", 10)?;
+    let seed: u64 = match matches.opt_get("seed")? {
+        Some(seed) => seed,
+        None => thread_rng().gen(),
+    };
+    let json = match matches.opt_str("format").as_deref() {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(format) => return Err(format!("unknown format {:?}", format).into()),
+    };
+    // The text format is the bare cells, so it only reads back on boards of the default size.
+    if !json {
+        let is_default = |c: &BoardConfig| c.width == DEFAULT_BOARD_SIZE && c.height == DEFAULT_BOARD_SIZE;
+        let fits = match config {
+            Some(config) => is_default(&board_configs[config]),
+            None => board_configs.iter().all(is_default),
+        };
+        if !fits {
+            return Err(format!("the text format only fits {0}x{0} boards, use --format json", DEFAULT_BOARD_SIZE).into());
+        }
+    }
 
-    let f = fs::File::open("crossword.txt")?;
+    let f = fs::File::open(matches.opt_str("dictionary").unwrap_or_else(|| "crossword.txt".to_string()))?;
     let file = io::BufReader::new(f);
 
-    let mut word_lists: [Vec<Vec<u8>>; 4] = [Vec::new(), Vec::new(), Vec::new(), Vec::new()];
+    let mut word_lists: Vec<Vec<Vec<u8>>> = vec![Vec::new(); MAX_BOARD_SIZE - MIN_WORD_LEN + 1];
     'lines: for line in file.lines() {
         let word = line?;
         let mut indices: Vec<u8> = Vec::new();
@@ -248,54 +468,163 @@ fn main() -> Result<(), Box<dyn Error>> {
                 None => continue 'lines,
             }
         }
-        word_lists[indices.len() - 3].push(indices);
+        if (MIN_WORD_LEN..=MAX_BOARD_SIZE).contains(&indices.len()) {
+            word_lists[indices.len() - MIN_WORD_LEN].push(indices);
+        }
     }
 
-    let sets = word_lists.iter_mut().map(|word_list| {
-        word_list.sort();
-        Set::from_iter(word_list).unwrap()
-    }).collect::<Vec<Set>>();
+    let sets = word_sets(word_lists);
+    let two_gram_counters = two_gram_counters(&sets);
+
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    for _i in 0..count {
+        let mut filled = None;
+        for _ in 0..TRIES_PER_BOARD {
+            let config_index = match config {
+                Some(config) => config,
+                None => rng.gen_range(0, board_configs.len()),
+            };
+            let board_config = &board_configs[config_index];
+            let cells: HashSet<usize> = board_config.constraints.iter().flat_map(|c| c.cells.clone()).collect();
+            let context = Context {
+                sets: &sets,
+                constraints: board_config.constraints.clone(),
+                two_gram_counters: &two_gram_counters,
+                cells,
+            };
+
+            if let Some(board) = get_board(&context, vec![0; board_config.width * board_config.height], &mut rng) {
+                filled = Some((config_index, board));
+                break;
+            }
+            // The search is exhaustive, so the same config fails again.
+            if config.is_some() {
+                break;
+            }
+        }
 
-    let mut two_gram_counters = vec![
-        HashMap::new(),
-        HashMap::new(),
-        HashMap::new(),
-        HashMap::new(),
-    ];
+        let (config_index, board) = match filled {
+            Some(filled) => filled,
+            None => return Err("no board config could be filled with the dictionary".into()),
+        };
+        if json {
+            let record = BoardRecord::new(config_index, &board_configs[config_index], &board);
+            println!("{}", serde_json::to_string(&record)?);
+        } else {
+            let board_text: String = bytes_to_string(board);
+            println!("{},{}", config_index, board_text);
+        }
+    }
 
-    for i in 0..HIRAGANAS.len() {
-        for j in 0..HIRAGANAS.len() {
-            for (two_gram_counter, set) in two_gram_counters.iter_mut().zip(sets.iter()) {
-                two_gram_counter.insert([i as u8, j as u8], count_prefix(&set, vec![i as u8, j as u8]));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constraint(direction: Direction, cells: Vec<usize>, index: usize) -> Constraint {
+        Constraint { direction, cells, index }
+    }
+
+    fn config(width: usize, height: usize, constraints: Vec<Constraint>) -> BoardConfig {
+        BoardConfig { width, height, layout_id: None, constraints }
+    }
+
+    fn bundled_configs(json: &str) -> Vec<BoardConfig> {
+        let configs: Vec<BoardConfigFile> = serde_json::from_str(json).unwrap();
+        configs.into_iter().map(BoardConfig::from).collect()
+    }
+
+    fn to_bytes(word: &str) -> Vec<u8> {
+        word.chars().map(|c| HIRAGANAS.iter().position(|&h| h == c).unwrap() as u8 + 1).collect()
+    }
+
+    #[test]
+    fn bundled_configs_pass_check() {
+        for json in &[include_str!("../boards.json"), include_str!("../grosswords.json")] {
+            for (index, config) in bundled_configs(json).iter().enumerate() {
+                assert_eq!(config.check(), Ok(()), "config {}", index);
             }
         }
     }
 
-    let mut rng = thread_rng();
-
-    for _i in 0..10 {
-        let config_index = rng.gen_range(0, board_configs.len());
-        let constraints = &board_configs[config_index];
-        let cells: HashSet<usize> = constraints.iter().map(|c| c.cells.clone()).flatten().collect();
-        let context = Context {
-            sets: &sets,
-            constraints: constraints.to_vec(),
-            two_gram_counters: &two_gram_counters,
-            cells: cells,
-        };
+    #[test]
+    fn check_rejects_invalid_configs() {
+        let word = || constraint(Direction::Horizontal, vec![0, 1, 2], 1);
+        assert!(config(21, 6, vec![word()]).check().is_err());
+        assert!(config(6, 0, vec![word()]).check().is_err());
+        assert!(config(6, 6, vec![constraint(Direction::Horizontal, vec![0, 1], 1)]).check().is_err());
+        assert!(config(6, 6, vec![constraint(Direction::Vertical, vec![24, 30, 36], 1)]).check().is_err());
+        // The cell 7 is only on the diagonal word, so nothing would fill it.
+        let uncovered = vec![word(), constraint(Direction::Diagonal, vec![0, 7, 14], 2)];
+        assert!(config(6, 6, uncovered).check().is_err());
+        let covered = vec![
+            word(),
+            constraint(Direction::Horizontal, vec![6, 7, 8], 2),
+            constraint(Direction::Horizontal, vec![12, 13, 14], 3),
+            constraint(Direction::Diagonal, vec![0, 7, 14], 4),
+        ];
+        assert_eq!(config(6, 6, covered).check(), Ok(()));
+        assert_eq!(config(20, 20, vec![constraint(Direction::Vertical, (0..20).map(|y| y * 20 + 19).collect(), 1)]).check(), Ok(()));
+    }
 
-        let board = get_board(&context, [0; 36]);
-        if let Some(board) = board {
-            let board_text: String = bytes_to_string(board.to_vec());
-            println!("{},{}", config_index, board_text);
-            /*
-            for j in 0..6 {
-                println!("{}", &board_text[6 * 3 * j..6 * 3 * (j + 1)]);
-            }
-            println!("");
-            */
+    #[test]
+    fn board_record_lists_entries_by_clue() {
+        let board_config = config(4, 3, vec![
+            constraint(Direction::Vertical, vec![1, 5, 9], 2),
+            constraint(Direction::Horizontal, vec![0, 1, 2], 1),
+        ]);
+        let mut board = vec![0; 12];
+        for (&cell, letter) in [0, 1, 2, 5, 9].iter().zip(to_bytes("あいうかき")) {
+            board[cell] = letter;
         }
+        let record = serde_json::to_value(BoardRecord::new(1, &board_config, &board)).unwrap();
+        assert_eq!(record, serde_json::json!({
+            "layoutId": "crossword-board-2",
+            "config": 1,
+            "width": 4,
+            "height": 3,
+            "grid": [["あ", "い", "う", null], [null, "か", null, null], [null, "き", null, null]],
+            "entries": [
+                {"word": "あいう", "cells": [{"x": 0, "y": 0}, {"x": 1, "y": 0}, {"x": 2, "y": 0}], "direction": "horizontal", "clue": 1},
+                {"word": "いかき", "cells": [{"x": 1, "y": 0}, {"x": 1, "y": 1}, {"x": 1, "y": 2}], "direction": "vertical", "clue": 2},
+            ],
+        }));
+
+        let named = BoardConfig { layout_id: Some("grossword-board-I".to_string()), ..board_config };
+        assert_eq!(BoardRecord::new(1, &named, &board).layout_id, "grossword-board-I");
     }
 
-    Ok(())
+    #[test]
+    fn grossword_layouts_fill() {
+        // Every word of あ and い up to 6 letters. This only shows that the layouts can be filled at all,
+        // not how long the real dictionary takes.
+        let mut word_lists: Vec<Vec<Vec<u8>>> = vec![Vec::new(); MAX_BOARD_SIZE - MIN_WORD_LEN + 1];
+        for len in MIN_WORD_LEN..=6 {
+            for bits in 0..(1 << len) {
+                let word = (0..len).map(|i| if bits >> i & 1 == 0 { 'あ' } else { 'い' }).collect::<String>();
+                word_lists[len - MIN_WORD_LEN].push(to_bytes(&word));
+            }
+        }
+        let sets = word_sets(word_lists);
+        let two_gram_counters = two_gram_counters(&sets);
+        let mut rng = StdRng::seed_from_u64(0);
+
+        for board_config in bundled_configs(include_str!("../grosswords.json")) {
+            let context = Context {
+                sets: &sets,
+                constraints: board_config.constraints.clone(),
+                two_gram_counters: &two_gram_counters,
+                cells: board_config.constraints.iter().flat_map(|c| c.cells.clone()).collect(),
+            };
+            let board = get_board(&context, vec![0; board_config.width * board_config.height], &mut rng)
+                .unwrap_or_else(|| panic!("{:?} isn't filled", board_config.layout_id));
+            for constraint in &board_config.constraints {
+                let word: Vec<u8> = constraint.cells.iter().map(|&cell| board[cell]).collect();
+                assert!(sets[word.len() - MIN_WORD_LEN].contains(word));
+            }
+        }
+    }
 }